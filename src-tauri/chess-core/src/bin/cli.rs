@@ -0,0 +1,18 @@
+//! Terminal front end, to play and debug games without a display
+
+use chess_core::cli::{parse_args, play, USAGE};
+use std::io::IsTerminal;
+
+fn main() {
+    let stdout = std::io::stdout();
+    let options = match parse_args(std::env::args().skip(1), stdout.is_terminal()) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}\n{}", error, USAGE);
+            std::process::exit(2);
+        }
+    };
+    if let Err(error) = play(std::io::stdin().lock(), stdout, &options) {
+        eprintln!("error while playing: {:?}", error);
+    }
+}
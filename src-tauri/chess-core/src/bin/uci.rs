@@ -0,0 +1,8 @@
+//! Universal Chess Interface front end to the built-in engine
+
+fn main() {
+    let input = std::io::stdin().lock();
+    if let Err(error) = chess_core::uci_server::serve(input, std::io::stdout()) {
+        eprintln!("error while speaking UCI: {:?}", error);
+    }
+}
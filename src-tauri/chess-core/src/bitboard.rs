@@ -0,0 +1,418 @@
+//! Bitboard representation of the board, for fast attack lookups and legal move generation
+//!
+//! Each bitboard is a `u64` with one bit per square, a1 = bit 0, h1 = bit 7 and h8 = bit 63.
+//! Knight, king and pawn attacks come from tables built at compile time, sliding attacks use
+//! precomputed rays cut short at the first blocker, and legality is decided with check and
+//! pin masks rather than by trying each move out.
+
+use super::board::BoardState;
+use super::types::{Color, GameMeta, MoveType, Piece, Square};
+
+/// One bit per square
+pub type Bitboard = u64;
+
+const PAWN: usize = 0;
+const KNIGHT: usize = 1;
+const BISHOP: usize = 2;
+const ROOK: usize = 3;
+const QUEEN: usize = 4;
+const KING: usize = 5;
+
+/// Directions as (col, row) steps: the first four lead to higher square indices, the rest to lower
+const DIRECTIONS: [(i8, i8); 8] = [
+    (0, 1),   // north
+    (1, 0),   // east
+    (1, 1),   // north east
+    (-1, 1),  // north west
+    (0, -1),  // south
+    (-1, 0),  // west
+    (-1, -1), // south west
+    (1, -1),  // south east
+];
+const ROOK_DIRECTIONS: [usize; 4] = [0, 1, 4, 5];
+const BISHOP_DIRECTIONS: [usize; 4] = [2, 3, 6, 7];
+
+/// Squares reached by a single step along each vector from every square
+const fn step_attacks(vectors: &[(i8, i8)]) -> [Bitboard; 64] {
+    let mut table = [0; 64];
+    let mut sq = 0;
+    while sq < 64 {
+        let (col, row) = ((sq % 8) as i8, (sq / 8) as i8);
+        let mut i = 0;
+        while i < vectors.len() {
+            let (c, r) = (col + vectors[i].0, row + vectors[i].1);
+            if c >= 0 && c < 8 && r >= 0 && r < 8 {
+                table[sq] |= 1 << (r * 8 + c);
+            }
+            i += 1;
+        }
+        sq += 1;
+    }
+    table
+}
+
+/// Squares along each direction from every square, up to the edge of the board
+const fn rays() -> [[Bitboard; 64]; 8] {
+    let mut table = [[0; 64]; 8];
+    let mut dir = 0;
+    while dir < 8 {
+        let mut sq = 0;
+        while sq < 64 {
+            let (mut c, mut r) = ((sq % 8) as i8, (sq / 8) as i8);
+            loop {
+                c += DIRECTIONS[dir].0;
+                r += DIRECTIONS[dir].1;
+                if c < 0 || c > 7 || r < 0 || r > 7 {
+                    break;
+                }
+                table[dir][sq] |= 1 << (r * 8 + c);
+            }
+            sq += 1;
+        }
+        dir += 1;
+    }
+    table
+}
+
+static KNIGHT_ATTACKS: [Bitboard; 64] = step_attacks(&[
+    (2, 1),
+    (2, -1),
+    (1, 2),
+    (1, -2),
+    (-2, 1),
+    (-2, -1),
+    (-1, 2),
+    (-1, -2),
+]);
+static KING_ATTACKS: [Bitboard; 64] = step_attacks(&DIRECTIONS);
+/// Squares attacked by a pawn of each colour, indexed by [`side`]
+static PAWN_ATTACKS: [[Bitboard; 64]; 2] = [
+    step_attacks(&[(1, -1), (-1, -1)]),
+    step_attacks(&[(1, 1), (-1, 1)]),
+];
+static RAYS: [[Bitboard; 64]; 8] = rays();
+
+/// Index of a colour into per colour tables
+fn side(color: Color) -> usize {
+    match color {
+        Color::Black => 0,
+        Color::White => 1,
+    }
+}
+
+/// Bit index of a square
+fn index(square: Square) -> usize {
+    square.1 * 8 + square.0
+}
+
+/// Square of a bit index
+fn square(index: usize) -> Square {
+    (index % 8, index / 8)
+}
+
+/// Iterate over the indices of the set bits, lowest first
+fn bits(mut bitboard: Bitboard) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if bitboard == 0 {
+            return None;
+        }
+        let index = bitboard.trailing_zeros() as usize;
+        bitboard &= bitboard - 1;
+        Some(index)
+    })
+}
+
+/// Squares a slider reaches along one direction, stopping at (and including) the first piece
+fn ray_attacks(dir: usize, sq: usize, occupied: Bitboard) -> Bitboard {
+    let ray = RAYS[dir][sq];
+    let blockers = ray & occupied;
+    if blockers == 0 {
+        return ray;
+    }
+    let first = if dir < 4 {
+        blockers.trailing_zeros() as usize
+    } else {
+        63 - blockers.leading_zeros() as usize
+    };
+    ray ^ RAYS[dir][first]
+}
+
+fn rook_attacks(sq: usize, occupied: Bitboard) -> Bitboard {
+    ROOK_DIRECTIONS
+        .iter()
+        .fold(0, |acc, &dir| acc | ray_attacks(dir, sq, occupied))
+}
+
+fn bishop_attacks(sq: usize, occupied: Bitboard) -> Bitboard {
+    BISHOP_DIRECTIONS
+        .iter()
+        .fold(0, |acc, &dir| acc | ray_attacks(dir, sq, occupied))
+}
+
+/// Squares strictly between two squares on the same line, empty if they don't share one
+fn between(from: usize, to: usize) -> Bitboard {
+    for ray in &RAYS {
+        if ray[from] & (1 << to) != 0 {
+            return ray[from] & !ray[to] & !(1 << to);
+        }
+    }
+    0
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+/// The pieces as bitboards, along with the castling and en passant rights they can't show
+pub struct Bitboards {
+    /// Pieces by [`side`] and kind: pawn, knight, bishop, rook, queen then king
+    pieces: [[Bitboard; 6]; 2],
+    /// Rooks that can still castle with their king
+    castling: Bitboard,
+    /// The pawn that has just moved two squares and can be taken en passant
+    en_passant: Option<Square>,
+    /// Write castling as the king moving onto its rook, see [`GameMeta::chess960`]
+    chess960: bool,
+}
+
+impl Bitboards {
+    /// Bitboards for the board, with the en passant right and variant from the game metadata
+    pub fn new(board: &BoardState, meta: &GameMeta) -> Self {
+        Bitboards {
+            en_passant: meta.en_passant,
+            chess960: meta.chess960,
+            ..Bitboards::from(board)
+        }
+    }
+    /// Every piece of one colour
+    pub fn color(&self, color: Color) -> Bitboard {
+        self.pieces[side(color)].iter().fold(0, |acc, bb| acc | bb)
+    }
+    /// Every piece on the board
+    pub fn occupied(&self) -> Bitboard {
+        self.color(Color::White) | self.color(Color::Black)
+    }
+    /// Pieces of `by` attacking a square, counting only pieces still in `occupied`
+    fn attackers(&self, sq: usize, by: Color, occupied: Bitboard) -> Bitboard {
+        let theirs = &self.pieces[side(by)];
+        let straight = theirs[ROOK] | theirs[QUEEN];
+        let diagonal = theirs[BISHOP] | theirs[QUEEN];
+        let attackers = (PAWN_ATTACKS[side(by.opponent())][sq] & theirs[PAWN])
+            | (KNIGHT_ATTACKS[sq] & theirs[KNIGHT])
+            | (KING_ATTACKS[sq] & theirs[KING])
+            | (rook_attacks(sq, occupied) & straight)
+            | (bishop_attacks(sq, occupied) & diagonal);
+        attackers & occupied
+    }
+    /// Check if any piece of colour `by` attacks this square
+    pub fn attacked(&self, square: Square, by: Color) -> bool {
+        self.attackers(index(square), by, self.occupied()) != 0
+    }
+    /// Every legal move for one colour, as (source, target, type)
+    ///
+    /// A pawn reaching the last rank is a single move, with the choice of piece left to the caller.
+    pub fn legal_moves(&self, color: Color) -> Vec<(Square, Square, MoveType)> {
+        let mut moves = Vec::new();
+        let ours = &self.pieces[side(color)];
+        let Some(king) = bits(ours[KING]).next() else {
+            return moves;
+        };
+        let them = color.opponent();
+        let theirs = &self.pieces[side(them)];
+        let own = self.color(color);
+        let enemy = self.color(them);
+        let occupied = own | enemy;
+        let mut push = |from: usize, targets: Bitboard, quiet: MoveType| {
+            for to in bits(targets) {
+                let kind = if enemy & (1 << to) != 0 {
+                    MoveType::Capture
+                } else {
+                    quiet
+                };
+                moves.push((square(from), square(to), kind));
+            }
+        };
+
+        //* 1. the king can step anywhere not attacked once it has moved out of the way
+        let without_king = occupied & !(1 << king);
+        let king_targets = bits(KING_ATTACKS[king] & !own)
+            .filter(|&to| self.attackers(to, them, without_king & !(1 << to)) == 0)
+            .fold(0, |acc, to| acc | 1 << to);
+        push(king, king_targets, MoveType::Move);
+
+        //* 2. in check, other pieces must take the checker or block it, in double check they can't help
+        let checkers = self.attackers(king, them, occupied);
+        let check_mask = match checkers.count_ones() {
+            0 => !0,
+            1 => checkers | between(king, checkers.trailing_zeros() as usize),
+            _ => return moves,
+        };
+
+        //* 3. pinned pieces can only move along the line between the king and the pinning piece
+        let mut pin_masks = [!0; 64];
+        let snipers = (rook_attacks(king, 0) & (theirs[ROOK] | theirs[QUEEN]))
+            | (bishop_attacks(king, 0) & (theirs[BISHOP] | theirs[QUEEN]));
+        for sniper in bits(snipers) {
+            let line = between(king, sniper);
+            let blockers = line & occupied;
+            if blockers.count_ones() == 1 && blockers & own != 0 {
+                pin_masks[blockers.trailing_zeros() as usize] = line | 1 << sniper;
+            }
+        }
+
+        //* 4. knights and sliders
+        for from in bits(ours[KNIGHT]) {
+            push(
+                from,
+                KNIGHT_ATTACKS[from] & !own & check_mask & pin_masks[from],
+                MoveType::Move,
+            );
+        }
+        for from in bits(ours[BISHOP] | ours[QUEEN]) {
+            let targets = bishop_attacks(from, occupied) & !own & check_mask & pin_masks[from];
+            push(from, targets, MoveType::Move);
+        }
+        for from in bits(ours[ROOK] | ours[QUEEN]) {
+            let targets = rook_attacks(from, occupied) & !own & check_mask & pin_masks[from];
+            push(from, targets, MoveType::Move);
+        }
+
+        //* 5. pawns push onto empty squares, two from their starting rank, and capture diagonally
+        let (forward, start_row): (isize, usize) = match color {
+            Color::White => (8, 1),
+            Color::Black => (-8, 6),
+        };
+        for from in bits(ours[PAWN]) {
+            let mask = check_mask & pin_masks[from];
+            let captures = PAWN_ATTACKS[side(color)][from] & enemy & mask;
+            push(from, captures, MoveType::Capture);
+            let one = from.wrapping_add_signed(forward);
+            if one < 64 && occupied & (1 << one) == 0 {
+                push(from, (1 << one) & mask, MoveType::Move);
+                let two = one.wrapping_add_signed(forward);
+                if from / 8 == start_row && occupied & (1 << two) == 0 {
+                    push(from, (1 << two) & mask, MoveType::Double);
+                }
+            }
+        }
+
+        //* 6. en passant, tried out in full as taking the pawn can uncover the king along a rank
+        if let Some(pawn_square) = self.en_passant {
+            let captured = index(pawn_square);
+            if theirs[PAWN] & (1 << captured) != 0 {
+                let to = captured.wrapping_add_signed(forward);
+                for from in bits(PAWN_ATTACKS[side(them)][to] & ours[PAWN]) {
+                    let after = (occupied & !(1 << from) & !(1 << captured)) | 1 << to;
+                    if self.attackers(king, them, after) == 0 {
+                        moves.push((square(from), square(to), MoveType::EnPassant));
+                    }
+                }
+            }
+        }
+
+        //* 7. castling, never out of or through check, onto the c or g file
+        if checkers == 0 {
+            for rook in bits(self.castling & ours[ROOK]) {
+                // outside Chess960 the king and rook must start from their usual squares
+                let standard = king % 8 == 4 && (rook % 8 == 0 || rook % 8 == 7);
+                if rook / 8 != king / 8 || !(self.chess960 || standard) {
+                    continue;
+                }
+                let row_start = king - king % 8;
+                let (king_to, rook_to) = if rook < king {
+                    (row_start + 2, row_start + 3)
+                } else {
+                    (row_start + 6, row_start + 5)
+                };
+                let others = occupied & !(1 << king) & !(1 << rook);
+                let path =
+                    between(king, king_to) | between(rook, rook_to) | 1 << king_to | 1 << rook_to;
+                let king_path = between(king, king_to) | 1 << king_to;
+                if path & others == 0
+                    && bits(king_path).all(|sq| self.attackers(sq, them, others) == 0)
+                {
+                    let target = if self.chess960 { rook } else { king_to };
+                    moves.push((square(king), square(target), MoveType::Castle));
+                }
+            }
+        }
+        moves
+    }
+}
+
+impl From<&BoardState> for Bitboards {
+    /// Bitboards for the board, with castling rights from the unmoved kings and rooks
+    fn from(board: &BoardState) -> Self {
+        let mut bitboards = Bitboards::default();
+        let mut unmoved_kings = [None; 2];
+        for col in 0..8 {
+            for row in 0..8 {
+                let piece = board.get((col, row));
+                let Some(color) = piece.get_colour() else {
+                    continue;
+                };
+                let kind = match piece {
+                    Piece::Pawn(..) => PAWN,
+                    Piece::Knight(..) => KNIGHT,
+                    Piece::Bishop(..) => BISHOP,
+                    Piece::Rook(_, unmoved) => {
+                        if unmoved {
+                            bitboards.castling |= 1 << index((col, row));
+                        }
+                        ROOK
+                    }
+                    Piece::Queen(..) => QUEEN,
+                    Piece::King(_, unmoved, ..) => {
+                        if unmoved {
+                            unmoved_kings[side(color)] = Some(row);
+                        }
+                        KING
+                    }
+                    Piece::None => continue,
+                };
+                bitboards.pieces[side(color)][kind] |= 1 << index((col, row));
+            }
+        }
+        // a rook can only castle if its king hasn't moved and shares its rank
+        let mut castling = 0;
+        for color in [Color::Black, Color::White] {
+            if let Some(row) = unmoved_kings[side(color)] {
+                castling |=
+                    bitboards.castling & bitboards.pieces[side(color)][ROOK] & 0xFF << (row * 8);
+            }
+        }
+        bitboards.castling = castling;
+        bitboards
+    }
+}
+
+impl From<&Bitboards> for BoardState {
+    /// Put the pieces back on a board
+    ///
+    /// First move status follows FEN: pawns on their starting rank and pieces on their home rank
+    /// are unmoved, while kings and rooks are only unmoved if they can still castle.
+    fn from(bitboards: &Bitboards) -> Self {
+        let mut board = BoardState::default();
+        for color in [Color::Black, Color::White] {
+            let (home_row, pawn_row) = match color {
+                Color::White => (0, 1),
+                Color::Black => (7, 6),
+            };
+            let pieces = &bitboards.pieces[side(color)];
+            let can_castle = bitboards.castling & pieces[ROOK] != 0;
+            for (kind, bitboard) in pieces.iter().enumerate() {
+                for sq in bits(*bitboard) {
+                    let (col, row) = square(sq);
+                    let on_home_row = row == home_row;
+                    let piece = match kind {
+                        PAWN => Piece::Pawn(color, row == pawn_row),
+                        KNIGHT => Piece::Knight(color, on_home_row),
+                        BISHOP => Piece::Bishop(color, on_home_row),
+                        ROOK => Piece::Rook(color, bitboards.castling & (1 << sq) != 0),
+                        QUEEN => Piece::Queen(color, on_home_row),
+                        _ => Piece::King(color, can_castle, false, false),
+                    };
+                    board.set((col, row), piece);
+                }
+            }
+        }
+        board
+    }
+}
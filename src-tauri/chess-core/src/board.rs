@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 
 /// 8x8 array containing either pieces or nothing
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
 pub struct BoardState([[Piece; 8]; 8]);
 
 impl BoardState {
@@ -19,8 +20,8 @@ impl BoardState {
         board[2][0] = Piece::Bishop(white, true);
         board[3][0] = Piece::Queen(white, true);
         board[4][0] = Piece::King(white, true, false, false);
-        board[5][0] = Piece::Knight(white, true);
-        board[6][0] = Piece::Bishop(white, true);
+        board[5][0] = Piece::Bishop(white, true);
+        board[6][0] = Piece::Knight(white, true);
         board[7][0] = Piece::Rook(white, true);
         // set up black pieces
         board[0][7] = Piece::Rook(black, true);
@@ -49,6 +50,35 @@ impl BoardState {
     pub fn iter(&self) -> std::slice::Iter<'_, [Piece; 8]> {
         self.0.iter()
     }
+    /// Check if this side has nothing left but its king, so can never checkmate
+    pub fn lone_king(&self, color: Color) -> bool {
+        self.iter()
+            .flatten()
+            .filter(|piece| piece.get_colour() == Some(color))
+            .all(|piece| matches!(piece, Piece::King(..)))
+    }
+    /// Check if neither side could ever checkmate, whatever moves are played
+    ///
+    /// Covers K v K, K+B v K, K+N v K and endings where every bishop stands on the same colour.
+    pub fn insufficient_material(&self) -> bool {
+        let mut knights = 0;
+        let mut bishop_colours = [false; 2];
+        for (col, pieces) in self.iter().enumerate() {
+            for (row, piece) in pieces.iter().enumerate() {
+                match piece {
+                    Piece::King(..) | Piece::None => {}
+                    Piece::Knight(..) => knights += 1,
+                    Piece::Bishop(..) => bishop_colours[(col + row) % 2] = true,
+                    Piece::Pawn(..) | Piece::Rook(..) | Piece::Queen(..) => return false,
+                }
+            }
+        }
+        match knights {
+            0 => !(bishop_colours[0] && bishop_colours[1]),
+            1 => bishop_colours == [false; 2],
+            _ => false,
+        }
+    }
 }
 
 impl Default for BoardState {
@@ -0,0 +1,77 @@
+//! Chess960 (Fischer Random) starting positions
+//!
+//! The 960 arrays are numbered 0 to 959 as in Scharnagl's scheme, where 518 is the standard setup.
+//! <https://en.wikipedia.org/wiki/Fischer_random_chess_numbering_scheme>
+
+use anyhow::{anyhow, Result};
+use rand::RngExt;
+
+/// Number of possible starting arrays
+pub const POSITIONS: usize = 960;
+/// Number of the standard starting array
+pub const STANDARD: usize = 518;
+
+/// Files of the two knights among the five squares left once the bishops and queen are placed
+const KNIGHT_PLACEMENTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+/// White's back rank for a starting array, as FEN letters from the a to the h file
+///
+/// Black mirrors it on the 8th rank.
+pub fn back_rank(index: usize) -> Result<[char; 8]> {
+    if index >= POSITIONS {
+        return Err(anyhow!(
+            "Chess960 positions are numbered 0 to {}, not {}",
+            POSITIONS - 1,
+            index
+        ));
+    }
+    let mut rank = [' '; 8];
+    // bishops go on opposite colours, the first on a light square
+    let n = index;
+    rank[(n % 4) * 2 + 1] = 'B';
+    let n = n / 4;
+    rank[(n % 4) * 2] = 'B';
+    let n = n / 4;
+    // then the queen and knights take their pick of the empty squares, in order
+    let place = |rank: &mut [char; 8], nth: usize, piece: char| {
+        if let Some(file) = (0..8).filter(|&file| rank[file] == ' ').nth(nth) {
+            rank[file] = piece;
+        }
+    };
+    place(&mut rank, n % 6, 'Q');
+    let (first, second) = KNIGHT_PLACEMENTS[n / 6];
+    // the second knight counts from before the first is placed
+    place(&mut rank, second, 'N');
+    place(&mut rank, first, 'N');
+    // and the king goes between the rooks on the three squares left
+    for piece in ['R', 'K', 'R'] {
+        place(&mut rank, 0, piece);
+    }
+    Ok(rank)
+}
+
+/// FEN of a starting array, with both sides able to castle either way
+pub fn start_fen(index: usize) -> Result<String> {
+    let white: String = back_rank(index)?.iter().collect();
+    Ok(format!(
+        "{}/pppppppp/8/8/8/8/PPPPPPPP/{} w KQkq - 0 1",
+        white.to_ascii_lowercase(),
+        white
+    ))
+}
+
+/// Pick one of the starting arrays at random
+pub fn random_index() -> usize {
+    rand::rng().random_range(0..POSITIONS)
+}
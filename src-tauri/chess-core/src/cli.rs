@@ -0,0 +1,190 @@
+//! Terminal front end, for playing and debugging games over SSH without a display
+//!
+//! Moves are typed in SAN or coordinate form, i.e. Nf3 or g1f3, between commands such as
+//! `undo`, `fen` and `pgn`.
+
+use super::engine::best_move;
+use super::notation::check_suffix;
+use super::pgn::game_result;
+use super::position::Game;
+use super::types::{Color, EngineSettings, GameResult};
+use super::utils::{pretty_print_board, turn_into_colour, BoardStyle};
+use anyhow::{anyhow, Context, Result};
+use std::io::{BufRead, Write};
+
+/// Summary of the commands, shown by `help`
+const HELP: &str = "\
+moves   in SAN or coordinate form, i.e. Nf3, O-O, e7e8q or g1f3
+undo    take back the last move, and the computer's reply to it
+draw    claim a draw by threefold repetition or the fifty-move rule
+fen     show the position as FEN
+pgn     show the game so far as PGN
+new     start a new game
+flip    turn the board around
+help    show this help
+quit    leave";
+
+/// Usage of the command line options, shown when they can't be read
+pub const USAGE: &str = "\
+usage: tauri-chess-cli [options]
+  --computer <white|black>  let the computer play a side
+  --depth <n>               how many moves deep the computer searches
+  --black                   draw the board from Black's side
+  --ascii                   letters instead of chess symbols
+  --colour, --no-colour     shade the squares, or not";
+
+#[derive(Clone, Debug, PartialEq)]
+/// Settings chosen on the command line
+pub struct CliOptions {
+    /// How the board is drawn
+    pub style: BoardStyle,
+    /// Colour played by the computer, if playing against it
+    pub computer: Option<Color>,
+    /// How strongly the computer plays
+    pub engine: EngineSettings,
+}
+
+impl Default for CliOptions {
+    fn default() -> Self {
+        CliOptions {
+            style: BoardStyle::default(),
+            computer: None,
+            // the search runs on the only thread, so keep it short
+            engine: EngineSettings {
+                move_time: Some(2000),
+                ..EngineSettings::default()
+            },
+        }
+    }
+}
+
+/// Read the command line options, not counting the program name
+///
+/// Unless told otherwise the board faces the player when playing the computer, and is coloured
+/// when `terminal` is true.
+pub fn parse_args(args: impl IntoIterator<Item = String>, terminal: bool) -> Result<CliOptions> {
+    let mut options = CliOptions::default();
+    let mut colour = None;
+    let mut orientation = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--computer" => {
+                options.computer = match args.next().as_deref() {
+                    Some("white") => Some(Color::White),
+                    Some("black") => Some(Color::Black),
+                    other => {
+                        return Err(anyhow!("--computer takes white or black, not {:?}", other))
+                    }
+                }
+            }
+            "--depth" => {
+                let depth = args.next().context("--depth takes a number")?;
+                options.engine.depth = depth
+                    .parse()
+                    .ok()
+                    .filter(|depth| *depth > 0)
+                    .ok_or_else(|| anyhow!("--depth takes a number from 1, not {}", depth))?;
+            }
+            "--black" => orientation = Some(Color::Black),
+            "--ascii" => options.style.unicode = false,
+            "--colour" | "--color" => colour = Some(true),
+            "--no-colour" | "--no-color" => colour = Some(false),
+            other => return Err(anyhow!("unknown option {}", other)),
+        }
+    }
+    options.style.colour = colour.unwrap_or(terminal);
+    options.style.orientation = orientation.unwrap_or(match options.computer {
+        Some(Color::White) => Color::Black,
+        _ => Color::White,
+    });
+    Ok(options)
+}
+
+/// Describe the state of the game for the player to move
+fn status(game: &Game) -> String {
+    let meta = game.position().meta();
+    match meta.result {
+        GameResult::Ongoing => {
+            let check = if check_suffix(meta) == "+" {
+                ", in check"
+            } else {
+                ""
+            };
+            let draw = match meta.claimable_draw {
+                Some(reason) => format!(", able to claim a draw by {:?}", reason),
+                None => String::new(),
+            };
+            format!("{:?} to move{}{}", turn_into_colour(meta.turn), check, draw)
+        }
+        GameResult::WhiteWins(reason)
+        | GameResult::BlackWins(reason)
+        | GameResult::Draw(reason) => {
+            format!("{} {:?}", game_result(meta), reason)
+        }
+    }
+}
+
+/// Let the computer move if it plays the side to move, saying which move it chose
+fn computer_move(game: &mut Game, options: &CliOptions, output: &mut impl Write) -> Result<()> {
+    let position = game.position();
+    if options.computer != Some(position.turn()) || position.result().is_over() {
+        return Ok(());
+    }
+    let Some(mv) = best_move(position, &options.engine) else {
+        return Ok(());
+    };
+    game.play(mv.from, mv.to, mv.promotion)?;
+    if let Some(record) = game.history().moves.last() {
+        writeln!(output, "computer plays {}", record.san)?;
+    }
+    Ok(())
+}
+
+/// Play a game from commands and moves on `input`, until told to quit or the input ends
+pub fn play(input: impl BufRead, mut output: impl Write, options: &CliOptions) -> Result<()> {
+    let mut game = Game::new();
+    let mut style = options.style;
+    let mut lines = input.lines();
+    loop {
+        computer_move(&mut game, options, &mut output)?;
+        write!(
+            output,
+            "\n{}{}\n> ",
+            pretty_print_board(game.position().board(), &style),
+            status(&game)
+        )?;
+        output.flush()?;
+        let Some(line) = lines.next() else {
+            break;
+        };
+        let line = line.context("failed to read input")?;
+        match line.trim() {
+            "" => {}
+            "quit" | "exit" => break,
+            "help" => writeln!(output, "{}", HELP)?,
+            "fen" => writeln!(output, "{}", game.position().fen())?,
+            "pgn" => writeln!(output, "{}", game.pgn())?,
+            "flip" => style.orientation = style.orientation.opponent(),
+            "new" => game = Game::new(),
+            "draw" => {
+                if let Err(error) = game.claim_draw() {
+                    writeln!(output, "{:#}", error)?;
+                }
+            }
+            "undo" => {
+                // take back the computer's reply as well, so it is the player's turn again
+                if !game.undo_turn(options.computer) {
+                    writeln!(output, "there is no move to undo")?;
+                }
+            }
+            notation => {
+                if let Err(error) = game.play_notation(notation) {
+                    writeln!(output, "{}: {:#}", notation, error)?;
+                }
+            }
+        }
+    }
+    writeln!(output)?;
+    Ok(())
+}
@@ -0,0 +1,373 @@
+//! Chess clocks: time controls with increments and delays, and the clock that runs them
+//!
+//! Time controls are written as in the PGN `TimeControl` tag, in seconds, with stages separated
+//! by colons, i.e. `40/7200:3600` for 40 moves in two hours then an hour for the rest, or `300+2`
+//! for five minutes with a two second increment. As an extension, `d` gives a simple delay and
+//! `b` a Bronstein delay in place of the increment, i.e. `300d5`.
+
+use super::types::Color;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::Instant;
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+/// Time given back to a player for each move, in milliseconds
+pub enum Bonus {
+    #[default]
+    None,
+    /// Fischer increment, added after every move
+    Increment(u64),
+    /// Bronstein delay, giving back the time used on the move up to this much
+    Bronstein(u64),
+    /// Simple delay, waiting this long each move before the clock starts counting down
+    Delay(u64),
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+/// One period of a time control
+pub struct Stage {
+    /// Moves to be made in this period, or `None` for the rest of the game
+    pub moves: Option<u32>,
+    /// Time for the period, in milliseconds
+    pub time: u64,
+    /// Time given back each move
+    pub bonus: Bonus,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+/// The time a player has for the game, one stage after another
+///
+/// A last stage with a move count repeats, i.e. `40/7200` is 40 moves every two hours.
+pub struct TimeControl {
+    pub stages: Vec<Stage>,
+}
+
+impl TimeControl {
+    /// A single period for the whole game
+    pub fn sudden_death(time: u64, bonus: Bonus) -> Self {
+        TimeControl {
+            stages: vec![Stage {
+                moves: None,
+                time,
+                bonus,
+            }],
+        }
+    }
+}
+
+/// Parse a number of seconds into milliseconds
+fn parse_seconds(text: &str) -> Result<u64> {
+    let seconds: f64 = text
+        .parse()
+        .map_err(|_| anyhow!("invalid number of seconds '{}'", text))?;
+    if !seconds.is_finite() || seconds < 0.0 {
+        return Err(anyhow!("invalid number of seconds '{}'", text));
+    }
+    Ok((seconds * 1000.0).round() as u64)
+}
+
+/// Write milliseconds as seconds, without a fraction if there isn't one
+fn seconds(millis: u64) -> String {
+    if millis.is_multiple_of(1000) {
+        (millis / 1000).to_string()
+    } else {
+        format!("{}", millis as f64 / 1000.0)
+    }
+}
+
+impl FromStr for TimeControl {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let mut stages = Vec::new();
+        for field in text.trim().split(':') {
+            let (moves, rest) = match field.split_once('/') {
+                Some((moves, rest)) => {
+                    let moves: u32 = moves
+                        .parse()
+                        .ok()
+                        .filter(|moves| *moves > 0)
+                        .ok_or_else(|| anyhow!("invalid move count '{}'", moves))?;
+                    (Some(moves), rest)
+                }
+                None => (None, field),
+            };
+            let (time, bonus) = match rest.find(['+', 'd', 'b']) {
+                Some(i) => {
+                    let amount = parse_seconds(&rest[i + 1..])?;
+                    let bonus = match &rest[i..=i] {
+                        "+" => Bonus::Increment(amount),
+                        "d" => Bonus::Delay(amount),
+                        _ => Bonus::Bronstein(amount),
+                    };
+                    (&rest[..i], bonus)
+                }
+                None => (rest, Bonus::None),
+            };
+            let time = parse_seconds(time)?;
+            if time == 0 && bonus == Bonus::None {
+                return Err(anyhow!("time control stage '{}' has no time at all", field));
+            }
+            stages.push(Stage { moves, time, bonus });
+        }
+        Ok(TimeControl { stages })
+    }
+}
+
+impl Display for TimeControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let stages: Vec<String> = self
+            .stages
+            .iter()
+            .map(|stage| {
+                let mut text = match stage.moves {
+                    Some(moves) => format!("{}/{}", moves, seconds(stage.time)),
+                    None => seconds(stage.time),
+                };
+                match stage.bonus {
+                    Bonus::None => {}
+                    Bonus::Increment(amount) => text.push_str(&format!("+{}", seconds(amount))),
+                    Bonus::Delay(amount) => text.push_str(&format!("d{}", seconds(amount))),
+                    Bonus::Bronstein(amount) => text.push_str(&format!("b{}", seconds(amount))),
+                }
+                text
+            })
+            .collect();
+        write!(f, "{}", stages.join(":"))
+    }
+}
+
+impl TryFrom<String> for TimeControl {
+    type Error = anyhow::Error;
+
+    fn try_from(text: String) -> Result<Self> {
+        text.parse()
+    }
+}
+
+impl From<TimeControl> for String {
+    fn from(control: TimeControl) -> Self {
+        control.to_string()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+/// The time control for each player, who may be given different amounts of time
+pub struct TimeControls {
+    #[cfg_attr(feature = "specta", specta(type = String))]
+    pub white: TimeControl,
+    #[cfg_attr(feature = "specta", specta(type = String))]
+    pub black: TimeControl,
+}
+
+impl TimeControls {
+    /// Both players on the same time control
+    pub fn both(control: TimeControl) -> Self {
+        TimeControls {
+            white: control.clone(),
+            black: control,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+/// One player's side of the clock
+struct SideClock {
+    /// Time left in milliseconds, not counting the move in progress
+    remaining: u64,
+    /// Stage of the time control being played
+    stage: usize,
+    /// Moves made so far in this stage
+    moves: u32,
+}
+
+impl SideClock {
+    fn new(control: &TimeControl) -> Self {
+        SideClock {
+            remaining: control.stages.first().map_or(0, |stage| stage.time),
+            stage: 0,
+            moves: 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+/// What the clock shows at a moment in time
+pub struct ClockReading {
+    /// Milliseconds left to White
+    pub white: u64,
+    /// Milliseconds left to Black
+    pub black: u64,
+    /// The side whose clock is running
+    pub running: Option<Color>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+/// A chess clock, counting down the time of whichever player is to move
+///
+/// Every method takes the current time, so the clock can be read and run without a timer of its own.
+pub struct Clock {
+    controls: TimeControls,
+    white: SideClock,
+    black: SideClock,
+    /// The side whose clock is running
+    running: Option<Color>,
+    /// When the running clock was started, not saved so a loaded clock waits for [`Clock::resume`]
+    #[serde(skip)]
+    started: Option<Instant>,
+}
+
+impl Clock {
+    /// A clock with the full time of the first stage on each side, not yet running
+    pub fn new(controls: TimeControls) -> Self {
+        Clock {
+            white: SideClock::new(&controls.white),
+            black: SideClock::new(&controls.black),
+            controls,
+            running: None,
+            started: None,
+        }
+    }
+    /// The time controls being played
+    pub fn controls(&self) -> &TimeControls {
+        &self.controls
+    }
+    /// The side whose clock is running
+    pub fn running(&self) -> Option<Color> {
+        self.running
+    }
+    fn control(&self, color: Color) -> &TimeControl {
+        match color {
+            Color::White => &self.controls.white,
+            Color::Black => &self.controls.black,
+        }
+    }
+    fn side(&self, color: Color) -> &SideClock {
+        match color {
+            Color::White => &self.white,
+            Color::Black => &self.black,
+        }
+    }
+    fn side_mut(&mut self, color: Color) -> &mut SideClock {
+        match color {
+            Color::White => &mut self.white,
+            Color::Black => &mut self.black,
+        }
+    }
+    /// The stage a player is in
+    fn stage(&self, color: Color) -> Option<&Stage> {
+        self.control(color).stages.get(self.side(color).stage)
+    }
+    /// Milliseconds since the running clock was started
+    fn elapsed(&self, now: Instant) -> u64 {
+        self.started.map_or(0, |started| {
+            now.saturating_duration_since(started).as_millis() as u64
+        })
+    }
+    /// Milliseconds taken off a player's clock by the move in progress
+    fn used(&self, color: Color, now: Instant) -> u64 {
+        if self.running != Some(color) {
+            return 0;
+        }
+        let elapsed = self.elapsed(now);
+        match self.stage(color).map(|stage| stage.bonus) {
+            Some(Bonus::Delay(delay)) => elapsed.saturating_sub(delay),
+            _ => elapsed,
+        }
+    }
+    /// Milliseconds a player has left
+    pub fn remaining(&self, color: Color, now: Instant) -> u64 {
+        self.side(color)
+            .remaining
+            .saturating_sub(self.used(color, now))
+    }
+    /// Both players' time, and whose clock is running
+    pub fn reading(&self, now: Instant) -> ClockReading {
+        ClockReading {
+            white: self.remaining(Color::White, now),
+            black: self.remaining(Color::Black, now),
+            running: self.running,
+        }
+    }
+    /// The player who has run out of time, if either has
+    pub fn flagged(&self, now: Instant) -> Option<Color> {
+        [Color::White, Color::Black]
+            .into_iter()
+            .find(|color| self.remaining(*color, now) == 0)
+    }
+    /// Take the time used so far off the running clock, without any bonus
+    fn charge(&mut self, now: Instant) {
+        if let Some(color) = self.running {
+            let used = self.used(color, now);
+            let side = self.side_mut(color);
+            side.remaining = side.remaining.saturating_sub(used);
+        }
+    }
+    /// Run a player's clock, stopping the other
+    pub fn start(&mut self, color: Color, now: Instant) {
+        self.charge(now);
+        self.running = Some(color);
+        self.started = Some(now);
+    }
+    /// Stop both clocks
+    pub fn stop(&mut self, now: Instant) {
+        self.charge(now);
+        self.running = None;
+        self.started = None;
+    }
+    /// Carry on running a clock that was saved while running
+    pub fn resume(&mut self, now: Instant) {
+        if self.running.is_some() && self.started.is_none() {
+            self.started = Some(now);
+        }
+    }
+    /// The running player has moved: take off the time they used, add their bonus,
+    /// move them on to their next stage if they have made its moves, and start the
+    /// other player's clock
+    ///
+    /// Returns the milliseconds left to the player who moved.
+    pub fn press(&mut self, now: Instant) -> Option<u64> {
+        let color = self.running?;
+        let elapsed = self.elapsed(now);
+        let bonus = self.stage(color).map_or(0, |stage| match stage.bonus {
+            Bonus::Increment(increment) => increment,
+            Bonus::Bronstein(delay) => elapsed.min(delay),
+            Bonus::None | Bonus::Delay(_) => 0,
+        });
+        self.charge(now);
+        let stages = self.control(color).stages.clone();
+        let side = self.side_mut(color);
+        side.remaining += bonus;
+        side.moves += 1;
+        if let Some(stage) = stages.get(side.stage)
+            && stage.moves.is_some_and(|moves| side.moves >= moves)
+        {
+            // the last stage repeats if it has a move count of its own
+            side.stage = (side.stage + 1).min(stages.len() - 1);
+            side.moves = 0;
+            side.remaining += stages[side.stage].time;
+        }
+        let remaining = side.remaining;
+        self.running = Some(color.opponent());
+        self.started = Some(now);
+        Some(remaining)
+    }
+}
+
+/// Write milliseconds as H:MM:SS, as used by the PGN `%clk` command
+pub fn clock_time(millis: u64) -> String {
+    let seconds = millis / 1000;
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
@@ -0,0 +1,383 @@
+//! Computer opponent, searching the game tree with negamax alpha-beta
+//!
+//! <https://www.chessprogramming.org/Negamax>
+
+use super::position::Position;
+use super::types::{
+    Color, EngineSettings, GameResult, Move, MoveType, Piece, PieceKind, MAX_SKILL_LEVEL,
+};
+use rand::{Rng, RngExt};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// How far, in centipawns, each skill level below the highest may stray from the best move
+const SKILL_MARGIN: i64 = 50;
+
+/// Score for delivering checkmate, less the number of moves taken so quicker mates score higher
+const MATE: isize = 100_000;
+/// Bound beyond any real score
+const INFINITY: isize = MATE + 1;
+
+/// Bonus for each piece depending on where it stands, from white's side of the board
+/// with the 8th rank first
+///
+/// <https://www.chessprogramming.org/Simplified_Evaluation_Function>
+#[rustfmt::skip]
+const PAWN_TABLE: [[isize; 8]; 8] = [
+    [  0,   0,   0,   0,   0,   0,   0,   0],
+    [ 50,  50,  50,  50,  50,  50,  50,  50],
+    [ 10,  10,  20,  30,  30,  20,  10,  10],
+    [  5,   5,  10,  25,  25,  10,   5,   5],
+    [  0,   0,   0,  20,  20,   0,   0,   0],
+    [  5,  -5, -10,   0,   0, -10,  -5,   5],
+    [  5,  10,  10, -20, -20,  10,  10,   5],
+    [  0,   0,   0,   0,   0,   0,   0,   0],
+];
+#[rustfmt::skip]
+const KNIGHT_TABLE: [[isize; 8]; 8] = [
+    [-50, -40, -30, -30, -30, -30, -40, -50],
+    [-40, -20,   0,   0,   0,   0, -20, -40],
+    [-30,   0,  10,  15,  15,  10,   0, -30],
+    [-30,   5,  15,  20,  20,  15,   5, -30],
+    [-30,   0,  15,  20,  20,  15,   0, -30],
+    [-30,   5,  10,  15,  15,  10,   5, -30],
+    [-40, -20,   0,   5,   5,   0, -20, -40],
+    [-50, -40, -30, -30, -30, -30, -40, -50],
+];
+#[rustfmt::skip]
+const BISHOP_TABLE: [[isize; 8]; 8] = [
+    [-20, -10, -10, -10, -10, -10, -10, -20],
+    [-10,   0,   0,   0,   0,   0,   0, -10],
+    [-10,   0,   5,  10,  10,   5,   0, -10],
+    [-10,   5,   5,  10,  10,   5,   5, -10],
+    [-10,   0,  10,  10,  10,  10,   0, -10],
+    [-10,  10,  10,  10,  10,  10,  10, -10],
+    [-10,   5,   0,   0,   0,   0,   5, -10],
+    [-20, -10, -10, -10, -10, -10, -10, -20],
+];
+#[rustfmt::skip]
+const ROOK_TABLE: [[isize; 8]; 8] = [
+    [  0,   0,   0,   0,   0,   0,   0,   0],
+    [  5,  10,  10,  10,  10,  10,  10,   5],
+    [ -5,   0,   0,   0,   0,   0,   0,  -5],
+    [ -5,   0,   0,   0,   0,   0,   0,  -5],
+    [ -5,   0,   0,   0,   0,   0,   0,  -5],
+    [ -5,   0,   0,   0,   0,   0,   0,  -5],
+    [ -5,   0,   0,   0,   0,   0,   0,  -5],
+    [  0,   0,   0,   5,   5,   0,   0,   0],
+];
+#[rustfmt::skip]
+const QUEEN_TABLE: [[isize; 8]; 8] = [
+    [-20, -10, -10,  -5,  -5, -10, -10, -20],
+    [-10,   0,   0,   0,   0,   0,   0, -10],
+    [-10,   0,   5,   5,   5,   5,   0, -10],
+    [ -5,   0,   5,   5,   5,   5,   0,  -5],
+    [  0,   0,   5,   5,   5,   5,   0,  -5],
+    [-10,   5,   5,   5,   5,   5,   0, -10],
+    [-10,   0,   5,   0,   0,   0,   0, -10],
+    [-20, -10, -10,  -5,  -5, -10, -10, -20],
+];
+#[rustfmt::skip]
+const KING_TABLE: [[isize; 8]; 8] = [
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-30, -40, -40, -50, -50, -40, -40, -30],
+    [-20, -30, -30, -40, -40, -30, -30, -20],
+    [-10, -20, -20, -20, -20, -20, -20, -10],
+    [ 20,  20,   0,   0,   0,   0,  20,  20],
+    [ 20,  30,  10,   0,   0,  10,  30,  20],
+];
+
+/// Value of a piece on this square, material plus position
+fn piece_square_value(piece: &Piece, col: usize, row: usize) -> isize {
+    let table = match piece {
+        Piece::None => return 0,
+        Piece::Pawn(..) => &PAWN_TABLE,
+        Piece::Knight(..) => &KNIGHT_TABLE,
+        Piece::Bishop(..) => &BISHOP_TABLE,
+        Piece::Rook(..) => &ROOK_TABLE,
+        Piece::Queen(..) => &QUEEN_TABLE,
+        Piece::King(..) => &KING_TABLE,
+    };
+    // the tables are drawn from white's side, so flip them for black
+    let rank = match piece.get_colour() {
+        Some(Color::Black) => row,
+        _ => 7 - row,
+    };
+    piece.get_value().unwrap_or(0) + table[rank][col]
+}
+
+/// Static evaluation of the position, relative to the player to move
+pub fn evaluate(position: &Position) -> isize {
+    let mut score = 0;
+    for (col, pieces) in position.board().iter().enumerate() {
+        for (row, piece) in pieces.iter().enumerate() {
+            match piece.get_colour() {
+                Some(Color::White) => score += piece_square_value(piece, col, row),
+                Some(Color::Black) => score -= piece_square_value(piece, col, row),
+                None => {}
+            }
+        }
+    }
+    match position.turn() {
+        Color::White => score,
+        Color::Black => -score,
+    }
+}
+
+/// Legal moves for the player to move, best guesses first
+///
+/// With `captures_only` just captures and queen promotions are returned, for the quiescence search.
+fn candidate_moves(position: &Position, captures_only: bool) -> Vec<Move> {
+    let board = position.board();
+    let mut moves = position.legal_moves();
+    if captures_only {
+        moves.retain(|mv| match mv.promotion {
+            Some(kind) => kind == PieceKind::Queen,
+            None => matches!(mv.kind, MoveType::Capture | MoveType::EnPassant),
+        });
+    }
+    // most valuable victim, least valuable attacker
+    moves.sort_by_cached_key(|mv| {
+        let victim = match mv.kind {
+            MoveType::EnPassant => 100,
+            _ => board.get(mv.to).get_value().unwrap_or(0),
+        };
+        let attacker = board.get(mv.from).get_value().unwrap_or(0);
+        let promoted = mv
+            .promotion
+            .and_then(|kind| kind.piece(position.turn()).get_value())
+            .unwrap_or(0);
+        -(victim * 10 + promoted - attacker / 10)
+    });
+    moves
+}
+
+/// Score of a finished game for the player to move, `ply` moves from the root
+fn final_score(result: GameResult, ply: isize) -> Option<isize> {
+    match result {
+        GameResult::Ongoing => None,
+        GameResult::Draw(_) => Some(0),
+        // the game can only be won by the player who just moved
+        GameResult::WhiteWins(_) | GameResult::BlackWins(_) => Some(ply - MATE),
+    }
+}
+
+/// Limits and counters of a single search
+struct Search<'a> {
+    /// How hard to search
+    settings: EngineSettings,
+    /// Raised from outside to end the search early
+    stop: &'a AtomicBool,
+    /// When the search began, to keep within the time budget
+    start: Instant,
+    /// Positions visited so far
+    nodes: u64,
+    /// The node or time budget has run out, so any result is incomplete
+    stopped: bool,
+}
+
+impl<'a> Search<'a> {
+    fn new(settings: EngineSettings, stop: &'a AtomicBool) -> Self {
+        Search {
+            settings,
+            stop,
+            start: Instant::now(),
+            nodes: 0,
+            stopped: false,
+        }
+    }
+    /// Count a visited position, then check if the node or time budget has run out,
+    /// or the search has been told to stop
+    fn out_of_budget(&mut self) -> bool {
+        self.nodes += 1;
+        if !self.stopped {
+            self.stopped = self.stop.load(Ordering::Relaxed)
+                || self
+                    .settings
+                    .node_limit
+                    .is_some_and(|limit| self.nodes > limit)
+                || self
+                    .settings
+                    .move_time
+                    .is_some_and(|millis| self.start.elapsed() >= Duration::from_millis(millis));
+        }
+        self.stopped
+    }
+    /// Score every root move looking `depth` moves ahead, or `None` if the budget ran out first
+    ///
+    /// With `exact` each move gets its true score, otherwise moves that can't beat the
+    /// best found so far only get an upper bound, which is quicker.
+    fn search_root(
+        &mut self,
+        position: &mut Position,
+        moves: &[Move],
+        depth: u8,
+        exact: bool,
+    ) -> Option<Vec<isize>> {
+        let mut alpha = -INFINITY;
+        let mut scores = Vec::with_capacity(moves.len());
+        for mv in moves {
+            let undo = position.make(*mv);
+            let bound = if exact { -INFINITY } else { alpha };
+            let score = -self.negamax(position, depth - 1, -INFINITY, -bound, 1);
+            position.unmake(&undo);
+            if self.stopped {
+                return None;
+            }
+            alpha = alpha.max(score);
+            scores.push(score);
+        }
+        Some(scores)
+    }
+    /// Score the position for the player to move, looking `depth` moves ahead
+    fn negamax(
+        &mut self,
+        position: &mut Position,
+        depth: u8,
+        mut alpha: isize,
+        beta: isize,
+        ply: isize,
+    ) -> isize {
+        if self.out_of_budget() {
+            return 0;
+        }
+        if let Some(score) = final_score(position.result(), ply) {
+            return score;
+        }
+        if depth == 0 {
+            return self.quiescence(position, alpha, beta, ply);
+        }
+        for mv in candidate_moves(position, false) {
+            let undo = position.make(mv);
+            let score = -self.negamax(position, depth - 1, -beta, -alpha, ply + 1);
+            position.unmake(&undo);
+            if score >= beta {
+                return beta;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+    /// Keep searching captures until the position is quiet, so exchanges aren't cut off halfway
+    ///
+    /// <https://www.chessprogramming.org/Quiescence_Search>
+    fn quiescence(
+        &mut self,
+        position: &mut Position,
+        mut alpha: isize,
+        beta: isize,
+        ply: isize,
+    ) -> isize {
+        if self.out_of_budget() {
+            return 0;
+        }
+        if let Some(score) = final_score(position.result(), ply) {
+            return score;
+        }
+        let stand_pat = evaluate(position);
+        if stand_pat >= beta {
+            return beta;
+        }
+        alpha = alpha.max(stand_pat);
+        for mv in candidate_moves(position, true) {
+            let undo = position.make(mv);
+            let score = -self.quiescence(position, -beta, -alpha, ply + 1);
+            position.unmake(&undo);
+            if score >= beta {
+                return beta;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+}
+
+/// Pick one of the scored moves, best first, straying further from the best the lower the skill level
+fn choose_move(ranked: &[(Move, isize)], skill_level: u8, rng: &mut impl Rng) -> Move {
+    let weakness = i64::from(MAX_SKILL_LEVEL.saturating_sub(skill_level));
+    if weakness == 0 {
+        return ranked[0].0;
+    }
+    ranked
+        .iter()
+        .max_by_key(|(_, score)| score + rng.random_range(0..=weakness * SKILL_MARGIN) as isize)
+        .map_or(ranked[0].0, |(mv, _)| *mv)
+}
+
+/// Progress of the search, reported after each completed iteration
+#[derive(Clone, Copy, Debug)]
+pub struct SearchInfo {
+    /// Depth just completed
+    pub depth: u8,
+    /// Score of the best move, relative to the player to move
+    pub score: isize,
+    /// Positions visited so far
+    pub nodes: u64,
+    /// Time spent so far
+    pub elapsed: Duration,
+    /// Best move found so far
+    pub best: Move,
+}
+
+/// Number of moves until mate, negative if the player to move is getting mated,
+/// if this score is a forced mate
+pub fn mate_in(score: isize) -> Option<isize> {
+    if score.abs() <= MATE / 2 {
+        return None;
+    }
+    let moves = (MATE - score.abs() + 1) / 2;
+    Some(if score > 0 { moves } else { -moves })
+}
+
+/// Find a move for the player to move, deepening the search one move at a time
+/// until the depth, node or time limit of `settings` is reached
+///
+/// Returns `None` if the game is over.
+pub fn best_move(position: &Position, settings: &EngineSettings) -> Option<Move> {
+    search(position, settings, &AtomicBool::new(false), |_| {})
+}
+
+/// Search like [`best_move`], stopping early once `stop` is raised, and reporting
+/// each completed iteration to `on_iteration`
+pub fn search(
+    position: &Position,
+    settings: &EngineSettings,
+    stop: &AtomicBool,
+    mut on_iteration: impl FnMut(&SearchInfo),
+) -> Option<Move> {
+    if position.result().is_over() {
+        return None;
+    }
+    let mut ranked: Vec<(Move, isize)> = candidate_moves(position, false)
+        .into_iter()
+        .map(|mv| (mv, 0))
+        .collect();
+    if ranked.is_empty() {
+        return None;
+    }
+    let exact = settings.skill_level < MAX_SKILL_LEVEL; // weaker play picks from all the scores
+    let mut search = Search::new(*settings, stop);
+    let mut position = position.clone(); // moves are made and unmade on this copy
+    for depth in 1..=settings.depth.max(1) {
+        let moves: Vec<Move> = ranked.iter().map(|(mv, _)| *mv).collect();
+        let Some(scores) = search.search_root(&mut position, &moves, depth, exact) else {
+            break; // out of budget, so keep the last complete iteration
+        };
+        ranked = moves.into_iter().zip(scores).collect();
+        // best first, which is also the best order to search them in the next iteration
+        ranked.sort_by_key(|(_, score)| -score);
+        on_iteration(&SearchInfo {
+            depth,
+            score: ranked[0].1,
+            nodes: search.nodes,
+            elapsed: search.start.elapsed(),
+            best: ranked[0].0,
+        });
+        if mate_in(ranked[0].1).is_some() {
+            break; // a forced mate has been found, looking deeper won't change it
+        }
+    }
+    Some(choose_move(&ranked, settings.skill_level, &mut rand::rng()))
+}
@@ -0,0 +1,358 @@
+//! Forsyth–Edwards Notation (FEN) import and export
+//!
+//! Castling rights are read as X-FEN or Shredder-FEN, so Chess960 positions can be described too.
+//!
+//! <https://en.wikipedia.org/wiki/Forsyth%E2%80%93Edwards_Notation>
+//! <https://en.wikipedia.org/wiki/X-FEN>
+
+use super::board::BoardState;
+use super::types::{Color, GameMeta, Hist, KingMeta, Piece, Square};
+use super::utils::{coord_to_square, letter_to_row, turn_into_colour, under_threat};
+use anyhow::{anyhow, Result};
+
+/// FEN string of the standard starting position
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Convert a FEN piece letter into a piece, using the square to decide on its first move status
+fn char_to_piece(letter: char, square: Square) -> Option<Piece> {
+    let color = if letter.is_ascii_uppercase() {
+        Color::White
+    } else {
+        Color::Black
+    };
+    let (home_row, pawn_row) = match color {
+        Color::White => (0, 1),
+        Color::Black => (7, 6),
+    };
+    let on_home_row = square.1 == home_row;
+    Some(match letter.to_ascii_lowercase() {
+        'p' => Piece::Pawn(color, square.1 == pawn_row),
+        'n' => Piece::Knight(color, on_home_row),
+        'b' => Piece::Bishop(color, on_home_row),
+        'q' => Piece::Queen(color, on_home_row),
+        // kings and rooks only keep their first move if a castling right says so
+        'r' => Piece::Rook(color, false),
+        'k' => Piece::King(color, false, false, false),
+        _ => return None,
+    })
+}
+
+/// Convert a piece into its FEN letter
+fn piece_to_char(piece: &Piece) -> Option<char> {
+    let letter = match piece {
+        Piece::None => return None,
+        Piece::Pawn(..) => 'p',
+        Piece::King(..) => 'k',
+        Piece::Queen(..) => 'q',
+        Piece::Bishop(..) => 'b',
+        Piece::Knight(..) => 'n',
+        Piece::Rook(..) => 'r',
+    };
+    Some(match piece.get_colour() {
+        Some(Color::White) => letter.to_ascii_uppercase(),
+        _ => letter,
+    })
+}
+
+/// Colour and back rank row of a castling right, given by its FEN letter
+fn castling_side(right: char) -> (Color, usize) {
+    if right.is_ascii_uppercase() {
+        (Color::White, 0)
+    } else {
+        (Color::Black, 7)
+    }
+}
+
+/// Square of the rook a castling right, given by its FEN letter K, Q, k or q, would castle with,
+/// if it still can: the outermost unmoved rook on that side of an unmoved king
+pub fn castling_rook(board: &BoardState, right: char) -> Option<Square> {
+    let (color, row) = castling_side(right);
+    let king_col = (0..8)
+        .find(|&col| matches!(board.get((col, row)), Piece::King(c, true, ..) if c == color))?;
+    let unmoved_rook = |col: &usize| board.get((*col, row)) == Piece::Rook(color, true);
+    let col = match right.to_ascii_lowercase() {
+        'k' => (king_col + 1..8).rev().find(unmoved_rook),
+        'q' => (0..king_col).find(unmoved_rook),
+        _ => None,
+    }?;
+    Some((col, row))
+}
+
+/// Check if a castling right, given by its FEN letter, is still available on this board
+pub fn has_castling_right(board: &BoardState, right: char) -> bool {
+    castling_rook(board, right).is_some()
+}
+
+/// Write the castling rights field
+///
+/// X-FEN keeps to KQkq unless another rook stands further out than the castling rook, then it
+/// gives the rook's file instead, as Shredder-FEN always does.
+fn castling_field(board: &BoardState, shredder: bool) -> String {
+    let mut field = String::new();
+    for right in ['K', 'Q', 'k', 'q'] {
+        let Some((col, row)) = castling_rook(board, right) else {
+            continue;
+        };
+        let (color, _) = castling_side(right);
+        let kingside = right.eq_ignore_ascii_case(&'k');
+        let hidden = (0..8)
+            .filter(|&other| if kingside { other > col } else { other < col })
+            .any(|other| matches!(board.get((other, row)), Piece::Rook(c, _) if c == color));
+        if shredder || hidden {
+            let file = (b'a' + col as u8) as char;
+            field.push(match color {
+                Color::White => file.to_ascii_uppercase(),
+                Color::Black => file,
+            });
+        } else {
+            field.push(right);
+        }
+    }
+    if field.is_empty() {
+        field.push('-');
+    }
+    field
+}
+
+/// Parse a FEN string into a board and the game metadata that goes with it
+pub fn parse_fen(fen: &str) -> Result<(BoardState, GameMeta)> {
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    if fields.len() != 6 {
+        return Err(anyhow!(
+            "FEN needs 6 space separated fields, found {}",
+            fields.len()
+        ));
+    }
+    let mut board = BoardState::default();
+    let mut meta = GameMeta::default();
+
+    //* 1. piece placement, from the 8th rank down to the 1st
+    let ranks: Vec<&str> = fields[0].split('/').collect();
+    if ranks.len() != 8 {
+        return Err(anyhow!("FEN needs 8 ranks, found {}", ranks.len()));
+    }
+    let (mut white_king, mut black_king) = (None, None);
+    for (i, rank) in ranks.iter().enumerate() {
+        let row = 7 - i;
+        let mut col = 0;
+        for letter in rank.chars() {
+            if let Some(gap) = letter.to_digit(10) {
+                if !(1..=8).contains(&gap) {
+                    return Err(anyhow!(
+                        "invalid empty square count '{}' on rank {}",
+                        letter,
+                        row + 1
+                    ));
+                }
+                col += gap as usize;
+            } else {
+                if col > 7 {
+                    return Err(anyhow!("rank {} has more than 8 squares", row + 1));
+                }
+                let Some(piece) = char_to_piece(letter, (col, row)) else {
+                    return Err(anyhow!("invalid piece '{}' on rank {}", letter, row + 1));
+                };
+                match piece {
+                    Piece::Pawn(..) if row == 0 || row == 7 => {
+                        return Err(anyhow!("pawn found on back rank {}", row + 1));
+                    }
+                    Piece::King(Color::White, ..) if white_king.replace((col, row)).is_some() => {
+                        return Err(anyhow!("more than one white king"));
+                    }
+                    Piece::King(Color::Black, ..) if black_king.replace((col, row)).is_some() => {
+                        return Err(anyhow!("more than one black king"));
+                    }
+                    _ => {}
+                }
+                board.set((col, row), piece);
+                col += 1;
+            }
+        }
+        if col != 8 {
+            return Err(anyhow!(
+                "rank {} describes {} squares, expected 8",
+                row + 1,
+                col
+            ));
+        }
+    }
+    let Some(white_king) = white_king else {
+        return Err(anyhow!("no white king on the board"));
+    };
+    let Some(black_king) = black_king else {
+        return Err(anyhow!("no black king on the board"));
+    };
+
+    //* 2. side to move
+    let to_move = match fields[1] {
+        "w" => Color::White,
+        "b" => Color::Black,
+        other => return Err(anyhow!("invalid side to move '{}', expected w or b", other)),
+    };
+
+    //* 3. castling rights, restoring first move status of the kings and rooks involved
+    // KQkq name the outermost rook on that side of the king, a file letter names the rook itself
+    if fields[2] != "-" {
+        for right in fields[2].chars() {
+            let (color, row) = castling_side(right);
+            let letter = right.to_ascii_lowercase();
+            if !matches!(letter, 'k' | 'q' | 'a'..='h') {
+                return Err(anyhow!("invalid castling right '{}'", right));
+            }
+            let is_rook =
+                |col: &usize| matches!(board.get((*col, row)), Piece::Rook(c, _) if c == color);
+            let king_col = (0..8).find(|&col| board.get((col, row)).is_king(color));
+            let rook_col = king_col.and_then(|king_col| match letter {
+                'k' => (king_col + 1..8).rev().find(is_rook),
+                'q' => (0..king_col).find(is_rook),
+                file => letter_to_row(file)
+                    .ok()
+                    .filter(|col| *col != king_col && is_rook(col)),
+            });
+            let (Some(king_col), Some(rook_col)) = (king_col, rook_col) else {
+                return Err(anyhow!(
+                    "castling right '{}' needs a {:?} king and rook on rank {}",
+                    right,
+                    color,
+                    row + 1
+                ));
+            };
+            board.set((king_col, row), Piece::King(color, true, false, false));
+            board.set((rook_col, row), Piece::Rook(color, true));
+            // anything but the usual squares can only be Chess960
+            if king_col != 4 || (rook_col != 0 && rook_col != 7) {
+                meta.chess960 = true;
+            }
+        }
+    }
+
+    //* 4. en passant target, stored in the metadata as the square of the pawn that moved
+    if fields[3] != "-" {
+        let letters: Vec<char> = fields[3].chars().collect();
+        let (pawn_row, pawn_color) = match (letters.as_slice(), to_move) {
+            ([_, '6'], Color::White) => (4, Color::Black),
+            ([_, '3'], Color::Black) => (3, Color::White),
+            _ => {
+                return Err(anyhow!(
+                    "en passant square '{}' is not possible with {:?} to move",
+                    fields[3],
+                    to_move
+                ))
+            }
+        };
+        let col = letter_to_row(letters[0])
+            .map_err(|e| anyhow!("invalid en passant square '{}': {}", fields[3], e))?;
+        let pawn_sq = (col, pawn_row);
+        if board.get(pawn_sq) != Piece::Pawn(pawn_color, false) {
+            return Err(anyhow!(
+                "en passant square '{}' has no pawn on {} that could have just moved",
+                fields[3],
+                coord_to_square(pawn_sq)
+            ));
+        }
+        meta.en_passant = Some(pawn_sq);
+    }
+
+    //* 5. halfmove clock
+    meta.halfmove_clock = fields[4]
+        .parse()
+        .map_err(|_| anyhow!("invalid halfmove clock '{}'", fields[4]))?;
+
+    //* 6. fullmove number, which together with the side to move sets the turn
+    let fullmove: usize = fields[5]
+        .parse()
+        .map_err(|_| anyhow!("invalid fullmove number '{}'", fields[5]))?;
+    if fullmove == 0 {
+        return Err(anyhow!("fullmove number starts at 1"));
+    }
+    meta.turn = (fullmove - 1)
+        .checked_mul(2)
+        .and_then(|turn| turn.checked_add(usize::from(to_move == Color::Black)))
+        .ok_or_else(|| anyhow!("fullmove number '{}' is too large", fields[5]))?;
+
+    // the side that just moved can't have left its king in check
+    let (waiting_king, waiting_color) = match to_move {
+        Color::White => (black_king, Color::Black),
+        Color::Black => (white_king, Color::White),
+    };
+    if under_threat(waiting_king, waiting_color, &board) {
+        return Err(anyhow!(
+            "{:?} is in check but it is not their turn",
+            waiting_color
+        ));
+    }
+    meta.white_king = KingMeta {
+        piece: board.get(white_king),
+        square: white_king,
+    };
+    meta.black_king = KingMeta {
+        piece: board.get(black_king),
+        square: black_king,
+    };
+    meta.update_king_threat(&mut board);
+    meta.calc_score(&board);
+    meta.check_draws(&board, &Hist::default()); // the position may already be drawn
+    Ok((board, meta))
+}
+
+/// Write the board and the game metadata out as a FEN string, using X-FEN castling rights
+/// so Chess960 positions are written the same way as the standard ones wherever possible
+pub fn to_fen(board: &BoardState, meta: &GameMeta) -> String {
+    write_fen(board, meta, false)
+}
+
+/// Write the board and the game metadata out as a Shredder-FEN string, which gives
+/// castling rights as the files of the rooks, i.e. HAha for the standard setup
+pub fn to_shredder_fen(board: &BoardState, meta: &GameMeta) -> String {
+    write_fen(board, meta, true)
+}
+
+fn write_fen(board: &BoardState, meta: &GameMeta, shredder: bool) -> String {
+    //* 1. piece placement, from the 8th rank down to the 1st
+    let mut ranks: Vec<String> = Vec::new();
+    for row in (0..8).rev() {
+        let mut rank = String::new();
+        let mut gap = 0;
+        for col in 0..8 {
+            match piece_to_char(&board.get((col, row))) {
+                Some(letter) => {
+                    if gap > 0 {
+                        rank.push_str(&gap.to_string());
+                        gap = 0;
+                    }
+                    rank.push(letter);
+                }
+                None => gap += 1,
+            }
+        }
+        if gap > 0 {
+            rank.push_str(&gap.to_string());
+        }
+        ranks.push(rank);
+    }
+
+    //* 2. side to move
+    let to_move = match turn_into_colour(meta.turn) {
+        Color::White => "w",
+        Color::Black => "b",
+    };
+
+    //* 3. castling rights, from the first move status of the kings and rooks
+    let castling = castling_field(board, shredder);
+
+    //* 4. en passant target, the square the double moving pawn passed over
+    let en_passant = match meta.en_passant {
+        Some((col, row)) => coord_to_square((col, if row == 3 { 2 } else { 5 })),
+        None => "-".to_string(),
+    };
+
+    format!(
+        "{} {} {} {} {} {}",
+        ranks.join("/"),
+        to_move,
+        castling,
+        en_passant,
+        meta.halfmove_clock,
+        meta.turn / 2 + 1
+    )
+}
@@ -0,0 +1,196 @@
+//! Applying moves to the game, shared by every way a move can be made
+
+use super::board::BoardState;
+use super::notation::{check_suffix, move_to_san};
+use super::types::{Color, GameMeta, Hist, Move, MoveRecord, MoveType, Piece, Square};
+use super::utils::turn_into_colour;
+use super::zobrist::position_hash;
+
+/// Perform a validated move, then hand the turn over and record it in the history
+pub fn apply_move(board: &mut BoardState, meta: &mut GameMeta, history: &mut Hist, mv: Move) {
+    if history.hashes.is_empty() {
+        history.hashes.push(position_hash(board, meta)); // the starting position
+    }
+    let mut record = MoveRecord {
+        from: mv.from,
+        to: mv.to,
+        move_type: mv.kind,
+        piece: board.get(mv.from),
+        captured: Piece::None,
+        promotion: mv
+            .promotion
+            .map(|kind| kind.piece(turn_into_colour(meta.turn))),
+        san: move_to_san(mv, board, meta),
+        previous: *meta,
+        clock: None,
+    };
+    history.undone.clear(); // a new move replaces anything that could be redone
+    record.captured = make_move(board, meta, mv);
+    history.score.push(meta.score);
+    history.hashes.push(position_hash(board, meta));
+    meta.check_draws(board, history);
+    record.san.push_str(check_suffix(meta));
+    history.moves.push(record);
+}
+
+/// Where the king lands, and where the rook starts and lands, for a castling move
+///
+/// The king and rook always finish on the c and d files or the g and f files. Chess960 castling
+/// is written as the king moving onto its own rook, otherwise as the king moving to its square.
+pub fn castling_squares(mv: Move, chess960: bool) -> (Square, Square, Square) {
+    let row = mv.from.1;
+    let kingside = mv.to.0 > mv.from.0;
+    let rook_from = match (chess960, kingside) {
+        (true, _) => mv.to,
+        (false, true) => (7, row),
+        (false, false) => (0, row),
+    };
+    if kingside {
+        ((6, row), rook_from, (5, row))
+    } else {
+        ((2, row), rook_from, (3, row))
+    }
+}
+
+/// Make the board and metadata changes of a validated move and hand the turn over,
+/// without recording anything, returning any piece captured
+///
+/// Handles the extra board changes of castling, en passant, double moves and promotion, which
+/// needs the `promotion` piece for a pawn reaching the end of the board.
+pub fn make_move(board: &mut BoardState, meta: &mut GameMeta, mv: Move) -> Piece {
+    let Move {
+        from: source,
+        to: target,
+        kind: move_type,
+        promotion,
+    } = mv;
+    let turn = turn_into_colour(meta.turn);
+    let piece = board.get(source);
+    let mut captured = board.get(target);
+    let mover = piece.has_moved();
+    let mut destination = target;
+    meta.en_passant = None; // clear any previous en passant target
+    if move_type == MoveType::Castle {
+        // in Chess960 the king and rook may swap or stay put, so lift both before placing either
+        let (king_to, rook_from, rook_to) = castling_squares(mv, meta.chess960);
+        let rook = board.get(rook_from).has_moved();
+        board.set(source, Piece::None);
+        board.set(rook_from, Piece::None);
+        board.set(king_to, mover);
+        board.set(rook_to, rook);
+        captured = Piece::None;
+        destination = king_to;
+    } else {
+        board.set(target, Piece::None); // empty the destination square
+        board.set(source, Piece::None); // take moving out of its square
+        board.set(target, mover); // place moving in the new square
+    }
+    match move_type {
+        MoveType::Castle => {}
+        MoveType::EnPassant => {
+            captured = board.get((target.0, source.1));
+            board.set((target.0, source.1), Piece::None);
+        }
+        MoveType::Double => {
+            meta.en_passant = Some(target);
+        }
+        _ => {
+            // normal move or capture
+            if let Some(kind) = promotion
+                && mover.is_promotable_pawn(target)
+            {
+                board.set(target, kind.piece(turn));
+            }
+        }
+    }
+    // captures and pawn moves reset the count towards the fifty-move rule
+    if matches!(piece, Piece::Pawn(..)) || captured != Piece::None {
+        meta.halfmove_clock = 0;
+    } else {
+        meta.halfmove_clock += 1;
+    }
+    if mover.is_king(turn) {
+        match turn {
+            Color::Black => {
+                meta.black_king.piece = mover;
+                meta.black_king.square = destination;
+            }
+            Color::White => {
+                meta.white_king.piece = mover;
+                meta.white_king.square = destination;
+            }
+        }
+    }
+    meta.new_turn(board);
+    captured
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// What [`make_move`] changed, so [`unmake_move`] can put it back
+pub struct Unmake {
+    /// The move that was made
+    pub mv: Move,
+    /// The piece that moved, as it was before moving
+    pub piece: Piece,
+    /// Any piece that was taken
+    pub captured: Piece,
+    /// Game metadata from just before the move
+    pub previous: GameMeta,
+}
+
+/// Restore the board and metadata to how they were before [`make_move`]
+pub fn unmake_move(board: &mut BoardState, meta: &mut GameMeta, undo: &Unmake) {
+    let Move { from, to, kind, .. } = undo.mv;
+    if kind == MoveType::Castle {
+        let (king_to, rook_from, rook_to) = castling_squares(undo.mv, undo.previous.chess960);
+        let color = turn_into_colour(undo.previous.turn);
+        board.set(king_to, Piece::None);
+        board.set(rook_to, Piece::None);
+        board.set(from, undo.piece);
+        board.set(rook_from, Piece::Rook(color, true)); // castling needs an unmoved rook
+    } else {
+        board.set(to, Piece::None);
+        board.set(from, undo.piece);
+        match kind {
+            MoveType::EnPassant => board.set((to.0, from.1), undo.captured),
+            _ => board.set(to, undo.captured),
+        }
+    }
+    *meta = undo.previous;
+    // the kings on the board carry check flags too
+    board.set(meta.white_king.square, meta.white_king.piece);
+    board.set(meta.black_king.square, meta.black_king.piece);
+}
+
+/// Take back the last move, restoring the board, metadata and history to just before it
+///
+/// Returns false if there is no move to take back.
+pub fn undo_move(board: &mut BoardState, meta: &mut GameMeta, history: &mut Hist) -> bool {
+    let Some(record) = history.moves.pop() else {
+        return false;
+    };
+    history.score.pop();
+    history.hashes.pop();
+    let undo = Unmake {
+        mv: record.as_move(),
+        piece: record.piece,
+        captured: record.captured,
+        previous: record.previous,
+    };
+    unmake_move(board, meta, &undo);
+    history.undone.push(record);
+    true
+}
+
+/// Play the last move taken back by [`undo_move`] again
+///
+/// Returns false if there is no move to redo.
+pub fn redo_move(board: &mut BoardState, meta: &mut GameMeta, history: &mut Hist) -> bool {
+    let Some(record) = history.undone.pop() else {
+        return false;
+    };
+    let undone = std::mem::take(&mut history.undone);
+    apply_move(board, meta, history, record.as_move());
+    history.undone = undone;
+    true
+}
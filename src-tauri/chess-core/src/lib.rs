@@ -0,0 +1,27 @@
+//! Chess rules, notation and engine, with no ties to any UI
+//!
+//! Start from [`Game`] to play a game through, or [`Position`] to inspect a single position.
+
+pub mod bitboard;
+pub mod board;
+pub mod chess960;
+pub mod cli;
+pub mod clock;
+pub mod engine;
+pub mod fen;
+pub mod game;
+mod moves;
+pub mod notation;
+pub mod perft;
+pub mod pgn;
+mod pieces;
+mod position;
+pub mod save;
+pub mod types;
+pub mod uci;
+pub mod uci_server;
+mod unit_tests;
+pub mod utils;
+pub mod zobrist;
+
+pub use position::{Game, Position};
@@ -3,7 +3,6 @@
 use super::board::BoardState;
 use super::types::{Color, MoveList, MoveType, Piece};
 use super::utils::check_enemy;
-use crate::chess::utils::under_threat;
 
 pub fn pawn_move(
     sq: (usize, usize),
@@ -71,47 +70,6 @@ pub fn pawn_move(
     moves
 }
 
-/// check for en passant special moves available to this pawn
-pub fn en_passant_move(
-    sq: (usize, usize),
-    color: Color,
-    en_passant_target: (usize, usize),
-) -> MoveList {
-    // fill an array of possible move vectors
-    let mut moves: MoveList = Vec::new(); // start with empty movelist
-    match color {
-        Color::White => {
-            if sq.0 < 7 && sq.1 == 4 {
-                let (col, row) = (sq.0 + 1, sq.1 + 1);
-                if en_passant_target == (col, sq.1) {
-                    moves.push(((col, row), MoveType::EnPassant));
-                }
-            }
-            if sq.0 > 0 && sq.1 == 4 {
-                let (col, row) = (sq.0 - 1, sq.1 + 1);
-                if en_passant_target == (col, sq.1) {
-                    moves.push(((col, row), MoveType::EnPassant));
-                }
-            }
-        }
-        Color::Black => {
-            if sq.0 < 7 && sq.1 == 3 {
-                let (col, row) = (sq.0 + 1, sq.1 - 1);
-                if en_passant_target == (col, sq.1) {
-                    moves.push(((col, row), MoveType::EnPassant));
-                }
-            }
-            if sq.0 > 0 && sq.1 == 3 {
-                let (col, row) = (sq.0 - 1, sq.1 - 1);
-                if en_passant_target == (col, sq.1) {
-                    moves.push(((col, row), MoveType::EnPassant));
-                }
-            }
-        }
-    }
-    moves
-}
-
 pub fn rook_move(sq: (usize, usize), color: Color, board: &BoardState) -> MoveList {
     let mut moves: MoveList = Vec::new(); // start with empty movelist
     for add in 1..8 {
@@ -287,34 +245,3 @@ pub fn king_move(sq: (usize, usize), color: Color, board: &BoardState) -> MoveLi
     }
     moves
 }
-
-pub fn check_castling_moves(sq: (usize, usize), color: Color, board: &BoardState) -> MoveList {
-    if !board.get(sq).is_king(color) {
-        panic!("Wrong piece found when checking for king");
-    }
-    let mut moves: MoveList = Vec::new();
-    let first_move = board.get(sq) == Piece::King(color, true, false, false);
-    // check if castling available
-    if first_move {
-        // left side castle (ooo)
-        if board.get((0, sq.1)) == Piece::Rook(color, true)
-            && board.get((1, sq.1)) == Piece::None
-            && board.get((2, sq.1)) == Piece::None
-            && !under_threat((2, sq.1), color, board)
-            && board.get((3, sq.1)) == Piece::None
-            && !under_threat((3, sq.1), color, board)
-        {
-            moves.push(((2, sq.1), MoveType::Castle));
-        }
-        // right side castle (oo)
-        if board.get((7, sq.1)) == Piece::Rook(color, true)
-            && board.get((6, sq.1)) == Piece::None
-            && !under_threat((6, sq.1), color, board)
-            && board.get((5, sq.1)) == Piece::None
-            && !under_threat((5, sq.1), color, board)
-        {
-            moves.push(((6, sq.1), MoveType::Castle));
-        }
-    }
-    moves
-}
@@ -0,0 +1,254 @@
+//! Standard Algebraic Notation (SAN) for chess moves
+//!
+//! <https://en.wikipedia.org/wiki/Algebraic_notation_(chess)>
+
+use super::board::BoardState;
+use super::types::{Color, GameMeta, Move, MoveType, Piece, PieceKind, Square};
+use super::utils::{all_moves, coord_to_square, letter_to_row, turn_into_colour, valid_move};
+use anyhow::{anyhow, Result};
+use std::mem::discriminant;
+
+/// Letter used for this piece in notation, pawns don't have one
+pub fn piece_letter(piece: &Piece) -> Option<char> {
+    match piece {
+        Piece::King(..) => Some('K'),
+        Piece::Queen(..) => Some('Q'),
+        Piece::Rook(..) => Some('R'),
+        Piece::Bishop(..) => Some('B'),
+        Piece::Knight(..) => Some('N'),
+        Piece::Pawn(..) | Piece::None => None,
+    }
+}
+
+/// Check if two pieces are the same type and colour, ignoring any status flags
+fn same_kind(a: &Piece, b: &Piece) -> bool {
+    discriminant(a) == discriminant(b) && a.get_colour() == b.get_colour()
+}
+
+/// Write a legal move in SAN, without any check or mate suffix
+///
+/// Must be called before the move is made, as disambiguation depends on the other pieces
+pub fn move_to_san(mv: Move, board: &BoardState, meta: &GameMeta) -> String {
+    let Move {
+        from: source,
+        to: target,
+        kind: move_type,
+        promotion,
+    } = mv;
+    if move_type == MoveType::Castle {
+        return if target.0 > source.0 { "O-O" } else { "O-O-O" }.to_string();
+    }
+    let piece = board.get(source);
+    let capture = matches!(move_type, MoveType::Capture | MoveType::EnPassant);
+    let source_name = coord_to_square(source);
+    let mut san = String::new();
+    match piece_letter(&piece) {
+        None => {
+            if capture {
+                san.push_str(&source_name[..1]);
+            }
+        }
+        Some(letter) => {
+            san.push(letter);
+            // other pieces of the same type that could also reach the target
+            let mut rivals: Vec<Square> = Vec::new();
+            for col in 0..8 {
+                for row in 0..8 {
+                    let other = (col, row);
+                    if other != source
+                        && same_kind(&board.get(other), &piece)
+                        && valid_move(other, target, board, meta).is_some()
+                    {
+                        rivals.push(other);
+                    }
+                }
+            }
+            if !rivals.is_empty() {
+                if rivals.iter().all(|sq| sq.0 != source.0) {
+                    san.push_str(&source_name[..1]);
+                } else if rivals.iter().all(|sq| sq.1 != source.1) {
+                    san.push_str(&source_name[1..]);
+                } else {
+                    san.push_str(&source_name);
+                }
+            }
+        }
+    }
+    if capture {
+        san.push('x');
+    }
+    san.push_str(&coord_to_square(target));
+    if let Some(kind) = promotion {
+        san.push('=');
+        san.push(kind.letter());
+    }
+    san
+}
+
+/// Suffix marking check or mate for the player now to move, once a move has been made
+pub fn check_suffix(meta: &GameMeta) -> &'static str {
+    let king = match turn_into_colour(meta.turn) {
+        Color::White => meta.white_king.piece,
+        Color::Black => meta.black_king.piece,
+    };
+    match king {
+        Piece::King(_, _, _, true) => "#",
+        Piece::King(_, _, true, _) => "+",
+        _ => "",
+    }
+}
+
+/// Resolve a SAN string into a legal move for the player whose turn it is
+pub fn parse_san(san: &str, board: &BoardState, meta: &GameMeta) -> Result<Move> {
+    let text = san.trim_end_matches(['+', '#', '!', '?']);
+    let moves = all_moves(board, meta);
+
+    //* 1. castling
+    let kingside = match text {
+        "O-O" | "0-0" => Some(true),
+        "O-O-O" | "0-0-0" => Some(false),
+        _ => None,
+    };
+    if let Some(kingside) = kingside {
+        return moves
+            .into_iter()
+            .find(|(source, target, move_type)| {
+                *move_type == MoveType::Castle && (target.0 > source.0) == kingside
+            })
+            .map(|(from, to, kind)| Move {
+                from,
+                to,
+                kind,
+                promotion: None,
+            })
+            .ok_or_else(|| anyhow!("castling '{}' is not legal here", san));
+    }
+
+    //* 2. piece, disambiguation, target and promotion
+    let mut chars: Vec<char> = text.chars().filter(|c| !matches!(c, 'x' | '=')).collect();
+    let invalid = || anyhow!("'{}' is not valid notation", san);
+    let promotion = match chars.as_slice() {
+        [.., rank, letter] if rank.is_ascii_digit() && "QRBN".contains(*letter) => {
+            let kind = PieceKind::promotion(*letter);
+            chars.pop();
+            kind
+        }
+        _ => None,
+    };
+    let kind = match chars.first() {
+        Some(letter) if "KQRBN".contains(*letter) => Some(chars.remove(0)),
+        _ => None,
+    };
+    if chars.len() < 2 || chars.len() > 4 {
+        return Err(invalid());
+    }
+    let (hint, square) = chars.split_at(chars.len() - 2);
+    let target = (
+        letter_to_row(square[0]).map_err(|_| invalid())?,
+        match square[1].to_digit(10) {
+            Some(digit @ 1..=8) => digit as usize - 1,
+            _ => return Err(invalid()),
+        },
+    );
+    let mut from_col = None;
+    let mut from_row = None;
+    for letter in hint {
+        match letter.to_digit(10) {
+            Some(digit @ 1..=8) => from_row = Some(digit as usize - 1),
+            Some(_) => return Err(invalid()),
+            None => from_col = Some(letter_to_row(*letter).map_err(|_| invalid())?),
+        }
+    }
+
+    //* 3. find the single legal move that fits
+    let candidates: Vec<(Square, Square, MoveType)> = moves
+        .into_iter()
+        .filter(|(source, dest, move_type)| {
+            *dest == target
+                && *move_type != MoveType::Castle
+                && piece_letter(&board.get(*source)) == kind
+                && from_col.is_none_or(|col| source.0 == col)
+                && from_row.is_none_or(|row| source.1 == row)
+        })
+        .collect();
+    let (source, target, move_type) = match candidates.as_slice() {
+        [] => return Err(anyhow!("'{}' is not a legal move", san)),
+        [single] => *single,
+        _ => return Err(anyhow!("'{}' is ambiguous", san)),
+    };
+    let promotes = board.get(source).is_promotable_pawn(target);
+    match (promotes, promotion) {
+        (true, None) => Err(anyhow!("'{}' needs a promotion piece", san)),
+        (false, Some(_)) => Err(anyhow!("'{}' is not a promotion", san)),
+        _ => Ok(Move {
+            from: source,
+            to: target,
+            kind: move_type,
+            promotion,
+        }),
+    }
+}
+
+/// Write a move in UCI form, i.e. e2e4, e7e8q or e1g1 for castling
+pub fn move_to_uci(mv: Move) -> String {
+    let mut uci = coord_to_square(mv.from) + &coord_to_square(mv.to);
+    if let Some(kind) = mv.promotion {
+        uci.push(kind.letter().to_ascii_lowercase());
+    }
+    uci
+}
+
+/// Resolve a long algebraic move for the player whose turn it is
+///
+/// Accepts UCI form (e2e4, e7e8q, e1g1 for castling) as well as the hyphenated
+/// form with optional piece letter (e2-e4, Ng1-f3, e4xd5, e7-e8=Q).
+pub fn parse_uci(text: &str, board: &BoardState, meta: &GameMeta) -> Result<Move> {
+    let invalid = || anyhow!("'{}' is not valid long algebraic notation", text);
+    let mut chars: Vec<char> = text
+        .trim_end_matches(['+', '#', '!', '?'])
+        .chars()
+        .filter(|c| !matches!(c, '-' | 'x' | '='))
+        .collect();
+    if chars.first().is_some_and(|c| "KQRBN".contains(*c)) {
+        chars.remove(0); // the piece is already known from the source square
+    }
+    let promotion = match chars.len() {
+        4 => None,
+        5 => Some(PieceKind::promotion(chars[4]).ok_or_else(invalid)?),
+        _ => return Err(invalid()),
+    };
+    let mut squares = [(0, 0); 2];
+    for (square, pair) in squares.iter_mut().zip(chars[..4].chunks(2)) {
+        *square = (
+            letter_to_row(pair[0]).map_err(|_| invalid())?,
+            match pair[1].to_digit(10) {
+                Some(digit @ 1..=8) => digit as usize - 1,
+                _ => return Err(invalid()),
+            },
+        );
+    }
+    let [source, target] = squares;
+    let Some(move_type) = valid_move(source, target, board, meta) else {
+        return Err(anyhow!("'{}' is not a legal move", text));
+    };
+    let promotes = board.get(source).is_promotable_pawn(target);
+    match (promotes, promotion) {
+        (true, None) => Err(anyhow!("'{}' needs a promotion piece", text)),
+        (false, Some(_)) => Err(anyhow!("'{}' is not a promotion", text)),
+        _ => Ok(Move {
+            from: source,
+            to: target,
+            kind: move_type,
+            promotion,
+        }),
+    }
+}
+
+/// Resolve a typed move in either SAN or long algebraic notation
+pub fn parse_move(text: &str, board: &BoardState, meta: &GameMeta) -> Result<Move> {
+    let text = text.trim();
+    match parse_san(text, board, meta) {
+        Ok(resolved) => Ok(resolved),
+        Err(san_error) => parse_uci(text, board, meta).map_err(|_| san_error),
+    }
+}
@@ -0,0 +1,39 @@
+//! Performance test (perft) of the move generator, counting every position reachable
+//! in a number of moves, to compare against known results
+//!
+//! <https://www.chessprogramming.org/Perft_Results>
+
+use super::notation::move_to_uci;
+use super::position::Position;
+
+/// Count the positions reached after `depth` moves from this one
+pub fn perft(position: &mut Position, depth: u8) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let moves = position.legal_moves();
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    let mut nodes = 0;
+    for mv in moves {
+        let undo = position.make(mv);
+        nodes += perft(position, depth - 1);
+        position.unmake(&undo);
+    }
+    nodes
+}
+
+/// Perft split up by the first move, in UCI notation, to narrow down where counts go wrong
+pub fn divide(position: &mut Position, depth: u8) -> Vec<(String, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+    let mut split = Vec::new();
+    for mv in position.legal_moves() {
+        let undo = position.make(mv);
+        split.push((move_to_uci(mv), perft(position, depth - 1)));
+        position.unmake(&undo);
+    }
+    split
+}
@@ -0,0 +1,225 @@
+//! Portable Game Notation (PGN) import and export of complete games
+//!
+//! <https://en.wikipedia.org/wiki/Portable_Game_Notation>
+
+use super::board::BoardState;
+use super::clock::{clock_time, TimeControls};
+use super::fen::parse_fen;
+use super::game::apply_move;
+use super::notation::parse_san;
+use super::types::{Color, GameMeta, GameResult, Hist};
+use super::utils::turn_into_colour;
+use anyhow::{anyhow, Result};
+
+/// Maximum line length of exported movetext
+const LINE_LENGTH: usize = 80;
+
+/// Result of the game in PGN form
+pub fn game_result(meta: &GameMeta) -> &'static str {
+    match meta.result {
+        GameResult::Ongoing => "*",
+        GameResult::WhiteWins(_) => "1-0",
+        GameResult::BlackWins(_) => "0-1",
+        GameResult::Draw(_) => "1/2-1/2",
+    }
+}
+
+/// Write out the game so far as PGN, with the Seven Tag Roster and SAN movetext
+///
+/// Timed games add their `TimeControl`, or one for each side if they differ, and the
+/// time left after each move as a `%clk` comment.
+pub fn export_pgn(history: &Hist, meta: &GameMeta, controls: Option<&TimeControls>) -> String {
+    let result = game_result(meta);
+    let mut pgn = String::new();
+    for (name, value) in [
+        ("Event", "Tauri Chess game"),
+        ("Site", "Tauri Chess"),
+        ("Date", "????.??.??"),
+        ("Round", "-"),
+        ("White", "?"),
+        ("Black", "?"),
+        ("Result", result),
+    ] {
+        pgn.push_str(&format!("[{} \"{}\"]\n", name, value));
+    }
+    if let Some(controls) = controls {
+        if controls.white == controls.black {
+            pgn.push_str(&format!("[TimeControl \"{}\"]\n", controls.white));
+        } else {
+            pgn.push_str(&format!("[WhiteTimeControl \"{}\"]\n", controls.white));
+            pgn.push_str(&format!("[BlackTimeControl \"{}\"]\n", controls.black));
+        }
+    }
+    if meta.chess960 {
+        pgn.push_str("[Variant \"Chess960\"]\n");
+    }
+    if let Some(fen) = &history.start_fen {
+        pgn.push_str("[SetUp \"1\"]\n");
+        pgn.push_str(&format!("[FEN \"{}\"]\n", fen));
+    }
+    pgn.push('\n');
+
+    // number each white move, and the first move if black started
+    let start_turn = meta.turn.saturating_sub(history.moves.len());
+    let mut tokens: Vec<String> = Vec::new();
+    for (i, record) in history.moves.iter().enumerate() {
+        let turn = start_turn + i;
+        match turn_into_colour(turn) {
+            Color::White => tokens.push(format!("{}.", turn / 2 + 1)),
+            Color::Black if i == 0 => tokens.push(format!("{}...", turn / 2 + 1)),
+            Color::Black => {}
+        }
+        tokens.push(record.san.clone());
+        if let Some(millis) = record.clock {
+            tokens.push(format!("{{[%clk {}]}}", clock_time(millis)));
+        }
+    }
+    tokens.push(result.to_string());
+
+    let mut line = String::new();
+    for token in tokens {
+        if !line.is_empty() && line.len() + 1 + token.len() > LINE_LENGTH {
+            pgn.push_str(&line);
+            pgn.push('\n');
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&token);
+    }
+    pgn.push_str(&line);
+    pgn.push('\n');
+    pgn
+}
+
+/// Read the value of each `[Name "Value"]` tag pair
+fn parse_tag(line: &str) -> Option<(String, String)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+    let (name, value) = inner.split_once(' ')?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    Some((
+        name.to_string(),
+        value.replace("\\\"", "\"").replace("\\\\", "\\"),
+    ))
+}
+
+/// Split movetext into tokens, dropping comments, variations and annotation glyphs
+fn movetext_tokens(movetext: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut chars = movetext.chars().peekable();
+    let mut variation_depth: usize = 0;
+    while let Some(letter) = chars.next() {
+        match letter {
+            '{' => {
+                // comment runs until the closing brace
+                for inner in chars.by_ref() {
+                    if inner == '}' {
+                        break;
+                    }
+                }
+            }
+            ';' => {
+                // comment runs until the end of the line
+                for inner in chars.by_ref() {
+                    if inner == '\n' {
+                        break;
+                    }
+                }
+            }
+            '(' => variation_depth += 1,
+            ')' => variation_depth = variation_depth.saturating_sub(1),
+            '$' => {
+                // numeric annotation glyph
+                while chars.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    chars.next();
+                }
+            }
+            _ if variation_depth > 0 => {}
+            _ if letter.is_whitespace() => {}
+            _ => {
+                token.push(letter);
+                if chars
+                    .peek()
+                    .is_none_or(|c| c.is_whitespace() || "{;($)".contains(*c))
+                {
+                    tokens.push(std::mem::take(&mut token));
+                }
+                continue;
+            }
+        }
+        if !token.is_empty() {
+            tokens.push(std::mem::take(&mut token));
+        }
+    }
+    tokens
+}
+
+/// Replay a PGN game onto a fresh board, validating every move on the way
+///
+/// The starting position is taken from the `FEN` tag if there is one, and a `Variant`
+/// tag of Chess960 switches to its castling rules.
+pub fn import_pgn(pgn: &str) -> Result<(BoardState, GameMeta, Hist)> {
+    let mut start_fen = None;
+    let mut chess960 = false;
+    let mut movetext = String::new();
+    for line in pgn.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            if let Some((name, value)) = parse_tag(line) {
+                match name.as_str() {
+                    "FEN" => start_fen = Some(value),
+                    "Variant" => {
+                        let variant = value.to_ascii_lowercase().replace([' ', '-'], "");
+                        chess960 = matches!(variant.as_str(), "chess960" | "fischerandom");
+                    }
+                    _ => {}
+                }
+            } else {
+                return Err(anyhow!("invalid tag pair '{}'", line));
+            }
+        } else if !line.starts_with('%') {
+            movetext.push_str(line);
+            movetext.push('\n');
+        }
+    }
+    let (mut board, mut meta) = match &start_fen {
+        Some(fen) => parse_fen(fen).map_err(|e| anyhow!("invalid FEN tag: {}", e))?,
+        None => (BoardState::new(), GameMeta::default()),
+    };
+    meta.chess960 |= chess960;
+    let mut history = Hist {
+        start_fen,
+        ..Default::default()
+    };
+
+    for token in movetext_tokens(&movetext) {
+        if matches!(token.as_str(), "1-0" | "0-1" | "1/2-1/2" | "*") {
+            break; // game termination marker
+        }
+        // move numbers may be written on their own or stuck to the move
+        let san = match token.rfind('.') {
+            Some(i) => &token[i + 1..],
+            None => token.as_str(),
+        };
+        if san.is_empty() {
+            continue;
+        }
+        let move_number = match turn_into_colour(meta.turn) {
+            Color::White => format!("{}.", meta.turn / 2 + 1),
+            Color::Black => format!("{}...", meta.turn / 2 + 1),
+        };
+        if meta.result.is_over() {
+            return Err(anyhow!(
+                "move {} '{}': the game is already over",
+                move_number,
+                token
+            ));
+        }
+        let mv = parse_san(san, &board, &meta)
+            .map_err(|e| anyhow!("move {} '{}': {}", move_number, token, e))?;
+        apply_move(&mut board, &mut meta, &mut history, mv);
+    }
+    Ok((board, meta, history))
+}
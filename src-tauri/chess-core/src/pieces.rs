@@ -1,9 +1,9 @@
 //! Chess pieces traits
 
 use super::board::BoardState;
-use super::moves::{bish_move, en_passant_move, king_move, knight_move, pawn_move, rook_move};
-use super::types::{Color, GameMeta, MoveList, Piece, Square};
-use super::utils::{remove_invalid_moves, under_threat};
+use super::moves::{bish_move, king_move, knight_move, pawn_move, rook_move};
+use super::types::{Color, GameMeta, MoveList, Piece, PieceKind, Square};
+use super::utils::{all_moves, under_threat};
 
 /// Request state information from a selected piece
 impl Piece {
@@ -27,14 +27,6 @@ impl Piece {
             Piece::Rook(color, ..) => rook_move(sq, *color, board),
         }
     }
-    /// Return if there is an available en passant move for this pawn
-    pub fn get_en_passant_moves(&self, square: Square, en_passant_target: Square) -> MoveList {
-        if let Piece::Pawn(color, ..) = self {
-            en_passant_move(square, *color, en_passant_target)
-        } else {
-            panic!("should only be targetting a pawn")
-        }
-    }
     /// Return this piece's color
     pub fn get_colour(&self) -> Option<Color> {
         match self {
@@ -76,6 +68,18 @@ impl Piece {
             Piece::Rook(..) => Some(479),
         }
     }
+    /// The kind of piece this is, if there is one
+    pub fn kind(&self) -> Option<PieceKind> {
+        Some(match self {
+            Piece::None => return None,
+            Piece::Pawn(..) => PieceKind::Pawn,
+            Piece::King(..) => PieceKind::King,
+            Piece::Queen(..) => PieceKind::Queen,
+            Piece::Bishop(..) => PieceKind::Bishop,
+            Piece::Knight(..) => PieceKind::Knight,
+            Piece::Rook(..) => PieceKind::Rook,
+        })
+    }
     /// Ask if this piece is a promotable pawn
     pub fn is_promotable_pawn(&self, square: Square) -> bool {
         if let Piece::Pawn(color, ..) = self {
@@ -104,28 +108,15 @@ impl Piece {
     }
     /// If this piece is a king, update its check and checkmate states
     ///
-    /// Modifies piece in place
-    pub fn king_threat(&mut self, location: &Square, board: &BoardState, meta: GameMeta) {
+    /// Modifies piece in place, and returns the number of legal moves left to its side
+    pub fn king_threat(&mut self, location: &Square, board: &BoardState, meta: GameMeta) -> usize {
+        let mut team_moves: usize = 0;
         if let Piece::King(color, first_move, ..) = self {
             let check = under_threat(*location, *color, board);
-            let mut team_moves: usize = 0;
-            for col in 0..8 {
-                for row in 0..8 {
-                    let piece = board.get((col, row));
-                    if piece.get_colour() == Some(*color) {
-                        let no_moves = remove_invalid_moves(
-                            piece.get_moves((col, row), board),
-                            (col, row),
-                            &meta,
-                            board,
-                        )
-                        .len();
-                        team_moves += no_moves;
-                    }
-                }
-            }
+            team_moves = all_moves(board, &meta).len();
             let mate = check && (team_moves == 0);
             *self = Piece::King(*color, *first_move, check, mate);
         }
+        team_moves
     }
 }
@@ -0,0 +1,402 @@
+//! The public face of the rules: a [`Position`] to look at and a [`Game`] to play
+
+use super::board::BoardState;
+use super::chess960;
+use super::clock::Clock;
+use super::fen::{parse_fen, to_fen, to_shredder_fen, START_FEN};
+use super::game::{apply_move, make_move, redo_move, undo_move, unmake_move, Unmake};
+use super::notation::parse_move;
+use super::pgn::{export_pgn, import_pgn};
+use super::types::{
+    Color, GameMeta, GameResult, Hist, Move, MoveList, PieceKind, Square, Termination,
+};
+use super::utils::{all_moves, piece_moves, turn_into_colour, valid_move};
+use super::zobrist::position_hash;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// Whether a square given from outside is on the board, so can be looked up
+fn on_board((col, row): Square) -> bool {
+    col < 8 && row < 8
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Where the pieces are, and the state of play around them: whose turn it is,
+/// castling and en passant rights, checks and the result
+pub struct Position {
+    board: BoardState,
+    meta: GameMeta,
+}
+
+impl Position {
+    /// The standard starting position
+    pub fn new() -> Self {
+        Position {
+            board: BoardState::new(),
+            meta: GameMeta::default(),
+        }
+    }
+    /// A Chess960 starting position, numbered 0 to 959 with 518 the standard setup
+    pub fn chess960(index: usize) -> Result<Self> {
+        let mut position = Position::from_fen(&chess960::start_fen(index)?)?;
+        position.set_chess960();
+        Ok(position)
+    }
+    /// Set up a position from a FEN string
+    ///
+    /// Castling rights may be given as X-FEN or Shredder-FEN, and any that need
+    /// the king or rook off their usual squares make it a Chess960 position.
+    pub fn from_fen(fen: &str) -> Result<Self> {
+        let (board, meta) = parse_fen(fen)?;
+        Ok(Position { board, meta })
+    }
+    /// Write the position as a FEN string, with X-FEN castling rights
+    pub fn fen(&self) -> String {
+        to_fen(&self.board, &self.meta)
+    }
+    /// Write the position as a Shredder-FEN string, with castling rights as rook files
+    pub fn shredder_fen(&self) -> String {
+        to_shredder_fen(&self.board, &self.meta)
+    }
+    /// Whether Chess960 castling rules apply
+    pub fn is_chess960(&self) -> bool {
+        self.meta.chess960
+    }
+    /// Play by Chess960 rules, which only changes castling to be written as the king
+    /// moving onto its own rook, as even the standard setup is one of the 960
+    pub fn set_chess960(&mut self) {
+        self.meta.chess960 = true;
+    }
+    /// The pieces on the board
+    pub fn board(&self) -> &BoardState {
+        &self.board
+    }
+    /// Whose turn it is, check status, draw counters and the result
+    pub fn meta(&self) -> &GameMeta {
+        &self.meta
+    }
+    /// The player to move
+    pub fn turn(&self) -> Color {
+        turn_into_colour(self.meta.turn)
+    }
+    /// Whether the game is still going, and if not how it ended
+    pub fn result(&self) -> GameResult {
+        self.meta.result
+    }
+    /// Zobrist hash, identifying the position for repetitions
+    pub fn hash(&self) -> u64 {
+        position_hash(&self.board, &self.meta)
+    }
+    /// Legal moves for the piece on this square, if it belongs to the player to move
+    pub fn moves_from(&self, square: Square) -> MoveList {
+        if !on_board(square) {
+            return MoveList::new();
+        }
+        piece_moves(square, &self.board, &self.meta)
+    }
+    /// Whether moving from `source` to `target` is legal and promotes a pawn, so needs a piece chosen
+    pub fn is_promotion(&self, source: Square, target: Square) -> bool {
+        on_board(source)
+            && on_board(target)
+            && self.board.get(source).is_promotable_pawn(target)
+            && valid_move(source, target, &self.board, &self.meta).is_some()
+    }
+    /// Every legal move for the player to move, including castling and en passant,
+    /// with each choice of promotion piece as a move of its own
+    ///
+    /// Moves are generated even once the game is over, see [`Position::result`].
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        for (from, to, kind) in all_moves(&self.board, &self.meta) {
+            if self.board.get(from).is_promotable_pawn(to) {
+                for promotion in PieceKind::PROMOTIONS {
+                    moves.push(Move {
+                        from,
+                        to,
+                        kind,
+                        promotion: Some(promotion),
+                    });
+                }
+            } else {
+                moves.push(Move {
+                    from,
+                    to,
+                    kind,
+                    promotion: None,
+                });
+            }
+        }
+        moves
+    }
+    /// The position after playing a legal move
+    pub fn apply(&self, mv: Move) -> Position {
+        let mut position = self.clone();
+        position.make(mv);
+        position
+    }
+    /// Play a legal move in place, returning what is needed to [`Position::unmake`] it
+    pub fn make(&mut self, mv: Move) -> Unmake {
+        let piece = self.board.get(mv.from);
+        let previous = self.meta;
+        let captured = make_move(&mut self.board, &mut self.meta, mv);
+        Unmake {
+            mv,
+            piece,
+            captured,
+            previous,
+        }
+    }
+    /// Take back the move [`Position::make`] just played
+    pub fn unmake(&mut self, undo: &Unmake) {
+        unmake_move(&mut self.board, &mut self.meta, undo);
+    }
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+/// A game being played: the current position, every move that led to it and the clock, if timed
+pub struct Game {
+    position: Position,
+    history: Hist,
+    #[serde(default)]
+    clock: Option<Clock>,
+}
+
+impl Game {
+    /// A new game from the standard starting position
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// A new Chess960 game, from the starting position numbered 0 to 959
+    pub fn chess960(index: usize) -> Result<Self> {
+        let position = Position::chess960(index)?;
+        let history = Hist {
+            start_fen: Some(position.fen()),
+            ..Default::default()
+        };
+        Ok(Game {
+            position,
+            history,
+            clock: None,
+        })
+    }
+    /// A new game from the position in a FEN string
+    pub fn from_fen(fen: &str) -> Result<Self> {
+        let position = Position::from_fen(fen)?;
+        let start_fen = position.fen();
+        let history = Hist {
+            start_fen: (start_fen != START_FEN).then_some(start_fen),
+            ..Default::default()
+        };
+        Ok(Game {
+            position,
+            history,
+            clock: None,
+        })
+    }
+    /// Replay a game from PGN
+    pub fn from_pgn(pgn: &str) -> Result<Self> {
+        let (board, meta, history) = import_pgn(pgn)?;
+        Ok(Game {
+            position: Position { board, meta },
+            history,
+            clock: None,
+        })
+    }
+    /// Write the game so far as PGN
+    pub fn pgn(&self) -> String {
+        let controls = self.clock.as_ref().map(Clock::controls);
+        export_pgn(&self.history, &self.position.meta, controls)
+    }
+    /// The position reached
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+    /// The moves played so far, and any taken back that could be played again
+    pub fn history(&self) -> &Hist {
+        &self.history
+    }
+    /// The clock of a timed game
+    pub fn clock(&self) -> Option<&Clock> {
+        self.clock.as_ref()
+    }
+    /// Play with a clock, starting it for the player to move
+    pub fn set_clock(&mut self, clock: Clock) {
+        self.clock = Some(clock);
+        self.update_clock(Instant::now());
+    }
+    /// Carry on running the clock of a game that was saved while it ran
+    pub fn resume_clock(&mut self) {
+        if let Some(clock) = &mut self.clock {
+            clock.resume(Instant::now());
+        }
+    }
+    /// Take the time used on the move in progress off the clock, which carries on running
+    pub fn charge_clock(&mut self) {
+        if let Some(clock) = &mut self.clock
+            && let Some(color) = clock.running()
+        {
+            clock.start(color, Instant::now());
+        }
+    }
+    /// End the game if the player to move has run out of time, returning who did
+    pub fn check_flag(&mut self) -> Option<Color> {
+        let now = Instant::now();
+        let color = self.clock.as_ref()?.flagged(now)?;
+        if self.position.meta.result.is_over() {
+            return None;
+        }
+        let Position { board, meta } = &mut self.position;
+        meta.time_out(color, board);
+        self.update_clock(now);
+        Some(color)
+    }
+    /// Run the clock of the player to move, or stop it once the game is over
+    fn update_clock(&mut self, now: Instant) {
+        let turn = self.position.turn();
+        let over = self.position.meta.result.is_over();
+        if let Some(clock) = &mut self.clock {
+            if over {
+                clock.stop(now);
+            } else if clock.running() != Some(turn) {
+                clock.start(turn, now);
+            }
+        }
+    }
+    /// Press the clock after a move, noting the time the mover has left in its record
+    fn press_clock(&mut self) {
+        let now = Instant::now();
+        let Some(clock) = &mut self.clock else {
+            return;
+        };
+        let remaining = clock.press(now);
+        if let Some(record) = self.history.moves.last_mut() {
+            record.clock = remaining;
+        }
+        self.update_clock(now);
+    }
+    /// Whether a draw by repetition or the fifty-move rule ends the game without being claimed
+    pub fn set_auto_claim_draws(&mut self, auto_claim_draws: bool) {
+        self.position.meta.auto_claim_draws = auto_claim_draws;
+    }
+    /// Check the player to move is allowed to move at all, and still has time to
+    fn ready_to_move(&mut self) -> Result<()> {
+        if self.check_flag().is_some() {
+            return Err(anyhow!("time has run out"));
+        }
+        if self.position.meta.result.is_over() {
+            return Err(anyhow!("the game is over"));
+        }
+        Ok(())
+    }
+    /// Move the piece on `source` to `target`, if that is legal
+    ///
+    /// A pawn reaching the end of the board must be given the piece it is `promotion` to,
+    /// see [`Position::is_promotion`].
+    pub fn play(
+        &mut self,
+        source: Square,
+        target: Square,
+        promotion: Option<PieceKind>,
+    ) -> Result<()> {
+        self.ready_to_move()?;
+        if let Some(square) = [source, target]
+            .into_iter()
+            .find(|square| !on_board(*square))
+        {
+            return Err(anyhow!("square {:?} is off the board", square));
+        }
+        let Position { board, meta } = &mut self.position;
+        let kind =
+            valid_move(source, target, board, meta).ok_or_else(|| anyhow!("illegal move"))?;
+        match (board.get(source).is_promotable_pawn(target), promotion) {
+            (true, None) => return Err(anyhow!("the pawn needs a piece to be promoted to")),
+            (true, Some(PieceKind::Pawn | PieceKind::King)) => {
+                return Err(anyhow!("a pawn can't be promoted to a pawn or king"));
+            }
+            (false, Some(_)) => return Err(anyhow!("only a pawn reaching the end is promoted")),
+            _ => {}
+        }
+        let mv = Move {
+            from: source,
+            to: target,
+            kind,
+            promotion,
+        };
+        apply_move(board, meta, &mut self.history, mv);
+        self.press_clock();
+        Ok(())
+    }
+    /// Make a move written in SAN or long algebraic notation, i.e. Nf3, exd5, e7e8q or O-O
+    pub fn play_notation(&mut self, notation: &str) -> Result<()> {
+        self.ready_to_move()?;
+        let Position { board, meta } = &mut self.position;
+        let mv = parse_move(notation, board, meta)?;
+        apply_move(board, meta, &mut self.history, mv);
+        self.press_clock();
+        Ok(())
+    }
+    /// Take back the last move, returning false if there isn't one
+    ///
+    /// The clock switches sides without any time given back.
+    pub fn undo(&mut self) -> bool {
+        let Position { board, meta } = &mut self.position;
+        let undone = undo_move(board, meta, &mut self.history);
+        self.update_clock(Instant::now());
+        undone
+    }
+    /// Play the last move taken back again, returning false if there isn't one
+    pub fn redo(&mut self) -> bool {
+        let Position { board, meta } = &mut self.position;
+        let redone = redo_move(board, meta, &mut self.history);
+        self.update_clock(Instant::now());
+        redone
+    }
+    /// Take back moves until it is the turn of the player against `computer` again, returning
+    /// false if there is no move to take back
+    ///
+    /// Only the computer's first move is taken back if it moved first, leaving it to move again.
+    pub fn undo_turn(&mut self, computer: Option<Color>) -> bool {
+        if !self.undo() {
+            return false;
+        }
+        if computer == Some(self.position.turn()) {
+            self.undo();
+        }
+        true
+    }
+    /// Play moves taken back again until it is the turn of the player against `computer`,
+    /// returning false if there is no move to play again
+    ///
+    /// The computer is left to move if its reply was never played.
+    pub fn redo_turn(&mut self, computer: Option<Color>) -> bool {
+        if !self.redo() {
+            return false;
+        }
+        if computer == Some(self.position.turn()) && !self.position.result().is_over() {
+            self.redo();
+        }
+        true
+    }
+    /// End the game in a draw, if the player to move is able to claim one
+    pub fn claim_draw(&mut self) -> Result<Termination> {
+        let Some(reason) = self.position.meta.claimable_draw.take() else {
+            return Err(anyhow!("there is no draw to claim"));
+        };
+        self.position.meta.result = GameResult::Draw(reason);
+        self.update_clock(Instant::now());
+        Ok(reason)
+    }
+    /// A player's clock has run out, losing them the game unless their opponent can't mate
+    pub fn time_out(&mut self, color: Color) {
+        let Position { board, meta } = &mut self.position;
+        meta.time_out(color, board);
+        self.update_clock(Instant::now());
+    }
+}
@@ -0,0 +1,61 @@
+//! Saved games: everything needed to carry on a game later, as versioned JSON
+//!
+//! The version is checked before anything else is read, so a file written by a newer release is
+//! refused rather than half understood.
+
+use super::position::Game;
+use super::types::GameOptions;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Version of the save format written by this release
+pub const VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A game written to disk, with the options it was being played with
+pub struct SavedGame {
+    /// Version of the save format
+    pub version: u32,
+    /// The moves, metadata, clock and result
+    pub game: Game,
+    /// Settings the game was started with, such as who the computer plays
+    #[serde(default)]
+    pub options: GameOptions,
+}
+
+impl SavedGame {
+    /// Save a game as it stands, charging the player to move for the time they have used so far
+    pub fn new(game: &Game, options: &GameOptions) -> Self {
+        let mut game = game.clone();
+        game.charge_clock();
+        SavedGame {
+            version: VERSION,
+            game,
+            options: options.clone(),
+        }
+    }
+    /// Write the save file
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("failed to write saved game")
+    }
+    /// Read a save file, starting the clock again from where it was saved
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).context("saved game is not valid JSON")?;
+        let version = value
+            .get("version")
+            .and_then(serde_json::Value::as_u64)
+            .ok_or_else(|| anyhow!("saved game has no version"))?;
+        if version == 0 || version > VERSION.into() {
+            return Err(anyhow!(
+                "saved game is version {}, but only up to version {} can be read",
+                version,
+                VERSION
+            ));
+        }
+        let mut saved: SavedGame =
+            serde_json::from_value(value).context("failed to read saved game")?;
+        saved.game.resume_clock();
+        Ok(saved)
+    }
+}
@@ -0,0 +1,505 @@
+//! Specific Types useful for a chess game
+
+use super::board::BoardState;
+use super::clock::TimeControls;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// Halfmoves without a capture or pawn move before a draw can be claimed
+pub const FIFTY_MOVE_LIMIT: usize = 100;
+/// Halfmoves without a capture or pawn move before the game is drawn
+pub const SEVENTY_FIVE_MOVE_LIMIT: usize = 150;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct GameMeta {
+    /// Game turn
+    pub turn: usize,
+    /// Game score as a relative sum of piece value
+    pub score: isize,
+    /// Number of halfmoves since the last capture or pawn advance
+    pub halfmove_clock: usize,
+    /// Register if a pawn that has done a double move in the last turn
+    pub en_passant: Option<Square>,
+    /// Metadata relating to the black King
+    pub black_king: KingMeta,
+    /// Metadata relating to the white King
+    pub white_king: KingMeta,
+    /// Register if the game is active or how it ended
+    pub result: GameResult,
+    /// Number of times the current position has occurred
+    pub repetitions: usize,
+    /// A draw the player to move is able to claim
+    pub claimable_draw: Option<Termination>,
+    /// Declare draws as soon as they could be claimed, instead of waiting for a claim
+    pub auto_claim_draws: bool,
+    /// Chess960 rules, where castling is written as the king moving onto its own rook
+    #[serde(default)]
+    pub chess960: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(default)]
+/// Settings chosen when starting a new game
+pub struct GameOptions {
+    /// Declare draws as soon as they could be claimed, instead of waiting for a claim
+    pub auto_claim_draws: bool,
+    /// Colour played by the computer, if playing against it
+    pub computer: Option<Color>,
+    /// How strongly the computer plays
+    pub engine: EngineSettings,
+    /// The position the game starts from
+    pub start: StartPosition,
+    /// Promote pawns to a queen without asking which piece
+    pub auto_queen: bool,
+    /// Time for each player, if the game is timed
+    pub time_control: Option<TimeControls>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+/// Which starting position a new game uses
+pub enum StartPosition {
+    #[default]
+    Standard,
+    /// One of the Chess960 arrays, numbered 0 to 959
+    Chess960(usize),
+    /// A Chess960 array picked at random
+    RandomChess960,
+}
+
+/// Highest skill level, which always plays the best move found
+pub const MAX_SKILL_LEVEL: u8 = 20;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(default)]
+/// Strength of the computer opponent, searching until the first limit is reached
+pub struct EngineSettings {
+    /// Deepest search, in moves by either player
+    pub depth: u8,
+    /// Most positions to look at per move
+    pub node_limit: Option<u64>,
+    /// Most time to think per move, in milliseconds
+    pub move_time: Option<u64>,
+    /// From 0, picking loosely among the better moves, up to 20, always playing the best one found
+    pub skill_level: u8,
+}
+
+impl Default for EngineSettings {
+    fn default() -> Self {
+        EngineSettings {
+            depth: 3,
+            node_limit: None,
+            move_time: Some(5000),
+            skill_level: MAX_SKILL_LEVEL,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+/// Outcome of the game, or that it is still being played
+pub enum GameResult {
+    #[default]
+    Ongoing,
+    WhiteWins(Termination),
+    BlackWins(Termination),
+    Draw(Termination),
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+/// Reason the game came to an end
+pub enum Termination {
+    /// The player to move is in check with no legal moves
+    Checkmate,
+    /// The player to move is not in check but has no legal moves
+    Stalemate,
+    /// The same position has occurred three times, and a draw was claimed
+    ThreefoldRepetition,
+    /// The same position has occurred five times
+    FivefoldRepetition,
+    /// Fifty moves by each player without a capture or pawn move, and a draw was claimed
+    FiftyMoveRule,
+    /// Seventy-five moves by each player without a capture or pawn move
+    SeventyFiveMoveRule,
+    /// Neither player has enough pieces left to checkmate
+    InsufficientMaterial,
+    /// The player ran out of time
+    Timeout,
+    /// The player ran out of time, but their opponent couldn't checkmate them
+    TimeoutVsInsufficientMaterial,
+}
+
+impl GameResult {
+    /// Has the game come to an end
+    pub fn is_over(&self) -> bool {
+        *self != GameResult::Ongoing
+    }
+    /// The game has been won by this colour
+    pub fn win_for(color: Color, reason: Termination) -> Self {
+        match color {
+            Color::White => GameResult::WhiteWins(reason),
+            Color::Black => GameResult::BlackWins(reason),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+pub struct Hist {
+    pub score: Vec<isize>,
+    /// Every move played so far, in order
+    pub moves: Vec<MoveRecord>,
+    /// FEN of the starting position, if the game didn't start from the standard one
+    pub start_fen: Option<String>,
+    /// Hash of the position before the first move and after every move since
+    pub hashes: Vec<u64>,
+    /// Moves taken back, the most recent last, ready to be redone
+    pub undone: Vec<MoveRecord>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+/// A move that has been played, with enough detail to write it out in notation
+pub struct MoveRecord {
+    /// Square the piece moved from
+    pub from: Square,
+    /// Square the piece moved to
+    pub to: Square,
+    /// What kind of move this was
+    pub move_type: MoveType,
+    /// The piece that moved, as it was before moving
+    pub piece: Piece,
+    /// Any piece that was taken by this move
+    pub captured: Piece,
+    /// The piece a pawn was promoted to
+    pub promotion: Option<Piece>,
+    /// Standard Algebraic Notation of the move
+    pub san: String,
+    /// Game metadata from just before the move, restored when it is taken back
+    pub previous: GameMeta,
+    /// Milliseconds left on the mover's clock after the move, in a timed game
+    #[serde(default)]
+    pub clock: Option<u64>,
+}
+
+impl MoveRecord {
+    /// The move as it was played
+    pub fn as_move(&self) -> Move {
+        Move {
+            from: self.from,
+            to: self.to,
+            kind: self.move_type,
+            promotion: self.promotion.and_then(|piece| piece.kind()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct KingMeta {
+    /// Clone of the King's Piece Struct
+    pub piece: Piece,
+    /// Current location of the King
+    pub square: Square,
+}
+
+impl GameMeta {
+    /// Check if the king of the player to move is under threat and update its status,
+    /// ending the game on checkmate or stalemate
+    pub fn update_king_threat(&mut self, board: &mut BoardState) {
+        let turn: Color = if self.turn.is_multiple_of(2) {
+            Color::White
+        } else {
+            Color::Black
+        };
+        let (team_moves, king) = match turn {
+            Color::White => {
+                // check white king status
+                let team_moves =
+                    self.white_king
+                        .piece
+                        .king_threat(&self.white_king.square, board, *self);
+                board.set(self.white_king.square, self.white_king.piece);
+                (team_moves, self.white_king.piece)
+            }
+            Color::Black => {
+                // check black king status
+                let team_moves =
+                    self.black_king
+                        .piece
+                        .king_threat(&self.black_king.square, board, *self);
+                board.set(self.black_king.square, self.black_king.piece);
+                (team_moves, self.black_king.piece)
+            }
+        };
+        self.result = match (team_moves, king.is_king_mate()) {
+            (0, Some(true)) => GameResult::win_for(turn.opponent(), Termination::Checkmate),
+            (0, _) => GameResult::Draw(Termination::Stalemate),
+            _ => GameResult::Ongoing,
+        };
+    }
+    /// Increment the turn to the next player, check state of both players and return if game end has occurred
+    pub fn update_turn(&mut self) {
+        self.turn += 1;
+    }
+    /// Set up new game
+    pub fn new_game(&mut self) {
+        self.score = 0;
+        self.turn = 0;
+        self.halfmove_clock = 0;
+        self.result = GameResult::Ongoing;
+        self.repetitions = 1;
+        self.claimable_draw = None;
+        self.white_king.piece = Piece::King(Color::White, true, false, false);
+        self.white_king.square = (4, 0);
+        self.black_king.piece = Piece::King(Color::Black, true, false, false);
+        self.black_king.square = (4, 7);
+    }
+    /// run all necessary board state cleanup to start a new turn
+    pub fn new_turn(&mut self, board: &mut BoardState) {
+        self.clear_king_threat(board); // a legal move never leaves the mover in check
+        self.update_turn(); // toggle who's turn it is to play
+        self.update_king_threat(board); // evaluate the start of next turn
+        self.calc_score(board); // calculate score
+    }
+    /// Clear the check from the king of the player to move, without generating any moves
+    fn clear_king_threat(&mut self, board: &mut BoardState) {
+        let king = if self.turn.is_multiple_of(2) {
+            &mut self.white_king
+        } else {
+            &mut self.black_king
+        };
+        if let Piece::King(color, first_move, ..) = king.piece {
+            king.piece = Piece::King(color, first_move, false, false);
+            board.set(king.square, king.piece);
+        }
+    }
+    /// Count how often the current position has occurred, then offer or declare any draw
+    /// by insufficient material, repetition or the fifty and seventy-five-move rules
+    pub fn check_draws(&mut self, board: &BoardState, history: &Hist) {
+        if let Some(current) = history.hashes.last() {
+            self.repetitions = history
+                .hashes
+                .iter()
+                .filter(|hash| *hash == current)
+                .count();
+        }
+        self.claimable_draw = None;
+        if self.result.is_over() {
+            return; // a mate on the last allowed move still counts
+        }
+        if board.insufficient_material() {
+            self.result = GameResult::Draw(Termination::InsufficientMaterial);
+        } else if self.repetitions >= 5 {
+            self.result = GameResult::Draw(Termination::FivefoldRepetition);
+        } else if self.halfmove_clock >= SEVENTY_FIVE_MOVE_LIMIT {
+            self.result = GameResult::Draw(Termination::SeventyFiveMoveRule);
+        } else if self.repetitions >= 3 {
+            self.offer_draw(Termination::ThreefoldRepetition);
+        } else if self.halfmove_clock >= FIFTY_MOVE_LIMIT {
+            self.offer_draw(Termination::FiftyMoveRule);
+        }
+    }
+    /// End the game for a player who ran out of time, which is a draw instead of a loss
+    /// if their opponent has only a king left
+    pub fn time_out(&mut self, color: Color, board: &BoardState) {
+        if self.result.is_over() {
+            return;
+        }
+        self.claimable_draw = None;
+        self.result = if board.lone_king(color.opponent()) {
+            GameResult::Draw(Termination::TimeoutVsInsufficientMaterial)
+        } else {
+            GameResult::win_for(color.opponent(), Termination::Timeout)
+        };
+    }
+    /// Make a draw available to claim, or declare it straight away if draws are claimed automatically
+    pub fn offer_draw(&mut self, reason: Termination) {
+        if self.auto_claim_draws {
+            self.result = GameResult::Draw(reason);
+        } else {
+            self.claimable_draw = Some(reason);
+        }
+    }
+    /// Update score based on value of pieces on the board
+    pub fn calc_score(&mut self, board: &BoardState) {
+        let (mut white, mut black) = (0, 0);
+        for col in board.iter() {
+            for piece in col {
+                if let Some(color) = piece.get_colour() {
+                    match color {
+                        Color::Black => black += piece.get_value().unwrap_or(0),
+                        Color::White => white += piece.get_value().unwrap_or(0),
+                    }
+                }
+            }
+        }
+        self.score = white - black;
+    }
+}
+
+impl Default for GameMeta {
+    fn default() -> Self {
+        GameMeta {
+            turn: 0,
+            score: 0,
+            halfmove_clock: 0,
+            en_passant: None,
+            result: GameResult::Ongoing,
+            repetitions: 1,
+            claimable_draw: None,
+            auto_claim_draws: false,
+            chess960: false,
+            white_king: KingMeta {
+                piece: Piece::King(Color::White, true, false, false),
+                square: (4, 0),
+            },
+            black_king: KingMeta {
+                piece: Piece::King(Color::Black, true, false, false),
+                square: (4, 7),
+            },
+        }
+    }
+}
+
+/// Square reference in column and row
+pub type Square = (usize, usize);
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[repr(u8)]
+/// Moves can take one of many special types
+pub enum MoveType {
+    Move,
+    Capture,
+    Castle,
+    EnPassant,
+    Double,
+}
+
+pub type MoveList = Vec<(Square, MoveType)>;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+/// A legal move, with everything needed to play it
+pub struct Move {
+    /// Square the piece moves from
+    pub from: Square,
+    /// Square the piece moves to
+    pub to: Square,
+    /// What kind of move this is
+    pub kind: MoveType,
+    /// The piece a pawn is promoted to, which must be given when it reaches the end of the board
+    pub promotion: Option<PieceKind>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub enum Color {
+    Black,
+    White,
+}
+
+impl Color {
+    /// The other player's colour
+    pub fn opponent(&self) -> Self {
+        match self {
+            Color::Black => Color::White,
+            Color::White => Color::Black,
+        }
+    }
+}
+
+/// Some pieces have special behaviour if they haven't moved yet
+pub type FirstMove = bool;
+/// Is the King in check, meaning we need to consider avaliable moves
+pub type Check = bool;
+/// Is the King in check with no means to get out of check
+pub type CheckMate = bool;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[repr(u8)]
+pub enum Piece {
+    #[default]
+    None,
+    Pawn(Color, FirstMove),
+    King(Color, FirstMove, Check, CheckMate),
+    Queen(Color, FirstMove),
+    Bishop(Color, FirstMove),
+    Knight(Color, FirstMove),
+    Rook(Color, FirstMove),
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+/// A type of piece, of either colour
+pub enum PieceKind {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    King,
+}
+
+impl PieceKind {
+    /// The pieces a pawn can be promoted to, best first
+    pub const PROMOTIONS: [PieceKind; 4] = [
+        PieceKind::Queen,
+        PieceKind::Rook,
+        PieceKind::Bishop,
+        PieceKind::Knight,
+    ];
+
+    /// A piece of this kind, in the state it would be once it has moved
+    pub fn piece(self, color: Color) -> Piece {
+        match self {
+            PieceKind::Pawn => Piece::Pawn(color, false),
+            PieceKind::Knight => Piece::Knight(color, false),
+            PieceKind::Bishop => Piece::Bishop(color, false),
+            PieceKind::Rook => Piece::Rook(color, false),
+            PieceKind::Queen => Piece::Queen(color, false),
+            PieceKind::King => Piece::King(color, false, false, false),
+        }
+    }
+    /// Letter used for this kind of piece in notation, P for a pawn
+    pub fn letter(self) -> char {
+        match self {
+            PieceKind::Pawn => 'P',
+            PieceKind::Knight => 'N',
+            PieceKind::Bishop => 'B',
+            PieceKind::Rook => 'R',
+            PieceKind::Queen => 'Q',
+            PieceKind::King => 'K',
+        }
+    }
+    /// The promotion piece for a letter in either case, i.e. N or n for a knight
+    pub fn promotion(letter: char) -> Option<Self> {
+        PieceKind::PROMOTIONS
+            .into_iter()
+            .find(|kind| kind.letter() == letter.to_ascii_uppercase())
+    }
+}
+
+impl Display for Piece {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let code = match *self {
+            Piece::None => "__",
+            Piece::Pawn(Color::White, ..) => "wP",
+            Piece::Pawn(Color::Black, ..) => "bP",
+            Piece::King(Color::White, ..) => "wK",
+            Piece::King(Color::Black, ..) => "bK",
+            Piece::Queen(Color::White, ..) => "wQ",
+            Piece::Queen(Color::Black, ..) => "bQ",
+            Piece::Bishop(Color::White, ..) => "wB",
+            Piece::Bishop(Color::Black, ..) => "bB",
+            Piece::Knight(Color::White, ..) => "wN",
+            Piece::Knight(Color::Black, ..) => "bN",
+            Piece::Rook(Color::White, ..) => "wR",
+            Piece::Rook(Color::Black, ..) => "bR",
+        };
+        write!(f, "{}", code)
+    }
+}
@@ -0,0 +1,340 @@
+//! Client for external engines speaking the Universal Chess Interface (UCI)
+//!
+//! <https://www.wbec-ridderkerk.nl/html/UCIProtocol.html>
+
+use super::board::BoardState;
+use super::game::make_move;
+use super::notation::{check_suffix, move_to_san, move_to_uci, parse_uci};
+use super::types::{GameMeta, Hist};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long to wait for the engine to answer `uci` and `isready`
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long the engine gets to exit by itself after `quit`
+const QUIT_TIMEOUT: Duration = Duration::from_secs(1);
+/// Longest a search limited by depth may run before the engine is told to stop
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(60);
+/// How much longer than its time a search may run before the engine is told to stop
+const MOVE_TIME_GRACE: Duration = Duration::from_secs(1);
+/// How long the engine gets to give its best move once told to stop
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Commands to the engine, shared so a search can be stopped while it is running
+type EngineInput = Arc<Mutex<Box<dyn Write + Send>>>;
+
+/// Send a single command to the engine
+fn write_command(input: &EngineInput, command: &str) -> Result<()> {
+    let mut input = input
+        .lock()
+        .map_err(|_| anyhow!("engine input is unavailable"))?;
+    writeln!(input, "{}", command).context("failed to write to engine")?;
+    input.flush().context("failed to write to engine")
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+/// The engine's opinion of the position, for the player to move
+pub enum UciScore {
+    /// Advantage in hundredths of a pawn
+    Centipawns(i32),
+    /// Mate in this many moves, negative if the player to move is getting mated
+    Mate(i32),
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+/// Progress report sent by the engine while it searches
+pub struct UciInfo {
+    /// Depth searched so far
+    pub depth: Option<u32>,
+    /// Score of the best line found
+    pub score: Option<UciScore>,
+    /// The line of play the engine expects
+    pub pv: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+/// How long the engine should search for
+pub enum UciLimit {
+    /// Search this many moves deep
+    Depth(u32),
+    /// Search for this many milliseconds
+    MoveTime(u64),
+}
+
+/// Read an `info` line, ignoring any fields other than the depth, score and pv
+pub fn parse_info(line: &str) -> Option<UciInfo> {
+    let mut tokens = line.split_whitespace();
+    if tokens.next()? != "info" {
+        return None;
+    }
+    let mut info = UciInfo::default();
+    while let Some(token) = tokens.next() {
+        match token {
+            "depth" => info.depth = tokens.next().and_then(|value| value.parse().ok()),
+            "score" => {
+                let kind = tokens.next();
+                let value = tokens.next().and_then(|value| value.parse().ok());
+                info.score = match (kind, value) {
+                    (Some("cp"), Some(value)) => Some(UciScore::Centipawns(value)),
+                    (Some("mate"), Some(value)) => Some(UciScore::Mate(value)),
+                    _ => None,
+                };
+            }
+            "pv" => info.pv = tokens.by_ref().map(String::from).collect(),
+            "string" => break, // free text runs to the end of the line
+            _ => {}
+        }
+    }
+    Some(info)
+}
+
+/// Read the move from a `bestmove` line, ignoring any ponder move
+pub fn parse_bestmove(line: &str) -> Option<String> {
+    let mut tokens = line.split_whitespace();
+    if tokens.next()? != "bestmove" {
+        return None;
+    }
+    tokens.next().map(String::from)
+}
+
+/// Write a line of UCI moves in SAN, playing them out from this position
+///
+/// Stops at the first move that isn't legal.
+pub fn line_to_san(line: &[String], board: &BoardState, meta: &GameMeta) -> Vec<String> {
+    let mut board = board.clone();
+    let mut meta = *meta;
+    let mut sans = Vec::new();
+    for text in line {
+        let Ok(mv) = parse_uci(text, &board, &meta) else {
+            break;
+        };
+        let mut san = move_to_san(mv, &board, &meta);
+        make_move(&mut board, &mut meta, mv);
+        san.push_str(check_suffix(&meta));
+        sans.push(san);
+    }
+    sans
+}
+
+#[derive(Clone)]
+/// Stops the engine's search from another thread, without waiting for the engine itself
+pub struct UciStopper {
+    input: EngineInput,
+}
+
+impl UciStopper {
+    /// Tell the engine to stop searching and give its best move so far
+    pub fn stop(&self) -> Result<()> {
+        write_command(&self.input, "stop")
+    }
+}
+
+/// A running UCI engine
+pub struct UciEngine {
+    /// Name the engine gave itself
+    pub name: String,
+    /// Commands to the engine
+    input: EngineInput,
+    /// Lines of output from the engine, read on their own thread
+    lines: Receiver<String>,
+    /// The engine process, if we started one
+    process: Option<Child>,
+    /// Whether the engine has been told to play Chess960
+    chess960: bool,
+}
+
+impl UciEngine {
+    /// Start an engine executable and wait until it is ready
+    pub fn spawn(path: &str) -> Result<Self> {
+        let mut process = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("failed to start engine '{}'", path))?;
+        let (Some(input), Some(output)) = (process.stdin.take(), process.stdout.take()) else {
+            return Err(anyhow!("no pipes to engine '{}'", path));
+        };
+        match Self::from_streams(output, input) {
+            Ok(mut engine) => {
+                engine.process = Some(process);
+                Ok(engine)
+            }
+            Err(error) => {
+                let _ = process.kill();
+                Err(error.context(format!("'{}' is not a UCI engine", path)))
+            }
+        }
+    }
+    /// Talk UCI over the engine's output and input streams, waiting until it is ready
+    pub fn from_streams(
+        output: impl Read + Send + 'static,
+        input: impl Write + Send + 'static,
+    ) -> Result<Self> {
+        let (sender, lines) = channel();
+        thread::spawn(move || {
+            for line in BufReader::new(output).lines().map_while(|line| line.ok()) {
+                if sender.send(line).is_err() {
+                    break; // nobody is listening any more
+                }
+            }
+        });
+        let mut engine = UciEngine {
+            name: String::from("Unknown engine"),
+            input: Arc::new(Mutex::new(Box::new(input))),
+            lines,
+            process: None,
+            chess960: false,
+        };
+        engine.send("uci")?;
+        loop {
+            let line = engine.read_line(HANDSHAKE_TIMEOUT)?;
+            if let Some(name) = line.strip_prefix("id name ") {
+                engine.name = name.trim().to_string();
+            } else if line.trim() == "uciok" {
+                break;
+            }
+        }
+        engine.wait_ready()?;
+        Ok(engine)
+    }
+    /// Send a single command
+    fn send(&mut self, command: &str) -> Result<()> {
+        write_command(&self.input, command)
+    }
+    /// Wait for the next line of output, for no longer than `timeout`
+    fn read_line(&self, timeout: Duration) -> Result<String> {
+        self.lines
+            .recv_timeout(timeout)
+            .map_err(|error| match error {
+                RecvTimeoutError::Timeout => anyhow!("engine stopped responding"),
+                RecvTimeoutError::Disconnected => anyhow!("engine has quit"),
+            })
+    }
+    /// A handle to stop searches with, while this engine is busy searching
+    pub fn stopper(&self) -> UciStopper {
+        UciStopper {
+            input: Arc::clone(&self.input),
+        }
+    }
+    /// Block until the engine has caught up with every command sent so far
+    pub fn wait_ready(&mut self) -> Result<()> {
+        self.send("isready")?;
+        while self.read_line(HANDSHAKE_TIMEOUT)?.trim() != "readyok" {}
+        Ok(())
+    }
+    /// Let the engine know the next position is from a different game
+    pub fn new_game(&mut self) -> Result<()> {
+        self.send("ucinewgame")?;
+        self.wait_ready()
+    }
+    /// Set up a game: its starting position and every move played since
+    ///
+    /// Chess960 games switch on the engine's `UCI_Chess960` option, so castling is
+    /// understood as the king taking its own rook.
+    pub fn set_position(&mut self, history: &Hist, chess960: bool) -> Result<()> {
+        if chess960 != self.chess960 {
+            self.send(&format!("setoption name UCI_Chess960 value {}", chess960))?;
+            self.chess960 = chess960;
+        }
+        let mut command = match &history.start_fen {
+            Some(fen) => format!("position fen {}", fen),
+            None => String::from("position startpos"),
+        };
+        if !history.moves.is_empty() {
+            command.push_str(" moves");
+            for record in &history.moves {
+                command.push(' ');
+                command.push_str(&move_to_uci(record.as_move()));
+            }
+        }
+        self.send(&command)
+    }
+    /// Search the position, handing each progress report to `on_info`, and return the
+    /// best move in UCI notation
+    ///
+    /// An engine still searching once its time is up, or after a minute searching to a depth, is
+    /// told to stop. The search fails if the engine doesn't then answer. If `on_info` fails, the
+    /// engine is stopped and its best move read before the error is returned, so it isn't taken
+    /// for the best move of the next search.
+    pub fn go(
+        &mut self,
+        limit: UciLimit,
+        mut on_info: impl FnMut(UciInfo) -> Result<()>,
+    ) -> Result<String> {
+        let (command, time) = match limit {
+            UciLimit::Depth(depth) => (format!("go depth {}", depth), SEARCH_TIMEOUT),
+            UciLimit::MoveTime(millis) => (
+                format!("go movetime {}", millis),
+                Duration::from_millis(millis) + MOVE_TIME_GRACE,
+            ),
+        };
+        // anything left over from an earlier search would be read as part of this one
+        while self.lines.try_recv().is_ok() {}
+        self.send(&command)?;
+        let mut deadline = Instant::now() + time;
+        let mut stopped = false;
+        let mut failed = None;
+        loop {
+            let wait = deadline.saturating_duration_since(Instant::now());
+            let line = match self.lines.recv_timeout(wait) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) if !stopped => {
+                    self.send("stop")?;
+                    stopped = true;
+                    deadline = Instant::now() + STOP_TIMEOUT;
+                    continue;
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(failed.unwrap_or_else(|| anyhow!("engine stopped responding")));
+                }
+                Err(RecvTimeoutError::Disconnected) => return Err(anyhow!("engine has quit")),
+            };
+            if let Some(info) = parse_info(&line) {
+                if failed.is_some() {
+                    continue;
+                }
+                if let Err(error) = on_info(info) {
+                    failed = Some(error);
+                    if !stopped {
+                        self.send("stop")?;
+                        stopped = true;
+                        deadline = Instant::now() + STOP_TIMEOUT;
+                    }
+                }
+            } else if let Some(best) = parse_bestmove(&line) {
+                return match failed {
+                    Some(error) => Err(error),
+                    None => Ok(best),
+                };
+            }
+        }
+    }
+}
+
+impl Drop for UciEngine {
+    /// Ask the engine to quit, stopping it by force if it doesn't
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        if let Some(mut process) = self.process.take() {
+            let start = Instant::now();
+            while matches!(process.try_wait(), Ok(None)) {
+                if start.elapsed() > QUIT_TIMEOUT {
+                    let _ = process.kill();
+                    break;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+            let _ = process.wait();
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! Universal Chess Interface (UCI) server, so chess GUIs and tournament managers can
+//! play against the built-in engine
+//!
+//! <https://www.wbec-ridderkerk.nl/html/UCIProtocol.html>
+
+use super::engine::{mate_in, search, SearchInfo};
+use super::notation::{move_to_uci, parse_uci};
+use super::perft::divide;
+use super::position::Position;
+use super::types::{Color, EngineSettings, GameMeta, MAX_SKILL_LEVEL};
+use super::utils::turn_into_colour;
+use anyhow::{anyhow, Context, Result};
+use std::io::{BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// Moves assumed to be left before the next time control, when the GUI doesn't say
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+/// Time kept back from each move, in milliseconds, to allow for communication delays
+const MOVE_OVERHEAD: u64 = 50;
+
+/// Set up the position from the arguments of a `position` command,
+/// i.e. `startpos moves e2e4 e7e5` or `fen <fen> moves e7e8q`
+///
+/// With `chess960` castling moves are the king taking its own rook, i.e. `e1h1`.
+pub fn parse_position(args: &str, chess960: bool) -> Result<Position> {
+    let (setup, moves) = args.split_once("moves").unwrap_or((args, ""));
+    let setup = setup.trim();
+    let mut position = match setup.strip_prefix("fen") {
+        Some(fen) => Position::from_fen(fen.trim())?,
+        None if setup == "startpos" => Position::new(),
+        None => return Err(anyhow!("unknown position '{}'", setup)),
+    };
+    if chess960 {
+        position.set_chess960();
+    }
+    for text in moves.split_whitespace() {
+        let mv = parse_uci(text, position.board(), position.meta())?;
+        position.make(mv);
+    }
+    Ok(position)
+}
+
+/// Turn the arguments of a `go` command into search limits for the player to move
+///
+/// Without a depth, node or time limit the search carries on until told to stop.
+pub fn parse_go(args: &str, meta: &GameMeta) -> EngineSettings {
+    let mut settings = EngineSettings {
+        depth: u8::MAX,
+        node_limit: None,
+        move_time: None,
+        skill_level: MAX_SKILL_LEVEL,
+    };
+    let white = turn_into_colour(meta.turn) == Color::White;
+    let (mut clock, mut increment, mut moves_to_go) = (None, 0, DEFAULT_MOVES_TO_GO);
+    let mut tokens = args.split_whitespace();
+    while let Some(token) = tokens.next() {
+        let value = match token {
+            "depth" | "nodes" | "movetime" | "wtime" | "btime" | "winc" | "binc" | "movestogo" => {
+                tokens.next().and_then(|value| value.parse::<u64>().ok())
+            }
+            _ => continue, // infinite, ponder and anything unknown
+        };
+        let Some(value) = value else {
+            continue;
+        };
+        match token {
+            "depth" => settings.depth = value.clamp(1, u64::from(u8::MAX)) as u8,
+            "nodes" => settings.node_limit = Some(value),
+            "movetime" => settings.move_time = Some(value),
+            "wtime" if white => clock = Some(value),
+            "btime" if !white => clock = Some(value),
+            "winc" if white => increment = value,
+            "binc" if !white => increment = value,
+            "movestogo" => moves_to_go = value.max(1),
+            _ => {}
+        }
+    }
+    // share out what is left on the clock between the moves still to play
+    if let Some(clock) = clock
+        && settings.move_time.is_none()
+    {
+        let budget = clock / moves_to_go + increment / 2;
+        settings.move_time = Some(budget.min(clock.saturating_sub(MOVE_OVERHEAD)).max(1));
+    }
+    settings
+}
+
+/// Write out the progress of a search as an `info` line
+fn info_line(info: &SearchInfo) -> String {
+    let score = match mate_in(info.score) {
+        Some(moves) => format!("mate {}", moves),
+        None => format!("cp {}", info.score),
+    };
+    format!(
+        "info depth {} score {} nodes {} time {} pv {}",
+        info.depth,
+        score,
+        info.nodes,
+        info.elapsed.as_millis(),
+        move_to_uci(info.best)
+    )
+}
+
+/// Send a line to the GUI
+fn send<W: Write>(output: &Mutex<W>, line: &str) -> Result<()> {
+    let mut output = output
+        .lock()
+        .map_err(|_| anyhow!("output to the GUI is unavailable"))?;
+    writeln!(output, "{}", line).context("failed to write to GUI")?;
+    output.flush().context("failed to write to GUI")
+}
+
+/// Stop any search in progress, waiting for it to send its best move
+fn finish_search(searching: &mut Option<JoinHandle<()>>, stop: &AtomicBool) {
+    stop.store(true, Ordering::Relaxed);
+    if let Some(handle) = searching.take() {
+        let _ = handle.join();
+    }
+}
+
+/// Answer UCI commands from `input` on `output`, until told to quit or the input ends
+///
+/// Searches run on their own thread, so `stop` and `isready` are answered while thinking.
+pub fn serve(input: impl BufRead, output: impl Write + Send + 'static) -> Result<()> {
+    let output = Arc::new(Mutex::new(output));
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut searching = None;
+    let mut position = Position::new();
+    let mut chess960 = false;
+    for line in input.lines() {
+        let line = line.context("failed to read from GUI")?;
+        let line = line.trim();
+        let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+        match command {
+            "uci" => {
+                send(
+                    &output,
+                    &format!("id name Tauri Chess {}", env!("CARGO_PKG_VERSION")),
+                )?;
+                send(&output, &format!("id author {}", env!("CARGO_PKG_AUTHORS")))?;
+                send(&output, "option name UCI_Chess960 type check default false")?;
+                send(&output, "uciok")?;
+            }
+            "isready" => send(&output, "readyok")?,
+            "setoption" => {
+                // the only option is UCI_Chess960, i.e. `name UCI_Chess960 value true`
+                if let Some((name, value)) = args.split_once(" value ")
+                    && name.trim_start_matches("name").trim() == "UCI_Chess960"
+                {
+                    chess960 = value.trim() == "true";
+                }
+            }
+            "ucinewgame" => {
+                finish_search(&mut searching, &stop);
+                position = Position::new();
+            }
+            "position" => {
+                finish_search(&mut searching, &stop);
+                match parse_position(args, chess960) {
+                    Ok(new_position) => position = new_position,
+                    Err(error) => send(&output, &format!("info string {}", error))?,
+                }
+            }
+            "go" if args.starts_with("perft") => {
+                // non-standard, but understood by most engines: count positions by first move
+                finish_search(&mut searching, &stop);
+                let depth = args["perft".len()..].trim().parse().unwrap_or(1);
+                let split = divide(&mut position, depth);
+                for (uci, nodes) in &split {
+                    send(&output, &format!("{}: {}", uci, nodes))?;
+                }
+                let total: u64 = split.iter().map(|(_, nodes)| nodes).sum();
+                send(&output, &format!("\nNodes searched: {}", total))?;
+            }
+            "go" => {
+                finish_search(&mut searching, &stop);
+                stop.store(false, Ordering::Relaxed);
+                let settings = parse_go(args, position.meta());
+                let position = position.clone();
+                let (stop, output) = (Arc::clone(&stop), Arc::clone(&output));
+                searching = Some(thread::spawn(move || {
+                    let best = search(&position, &settings, &stop, |info| {
+                        let _ = send(&output, &info_line(info));
+                    });
+                    let best = match best {
+                        Some(mv) => move_to_uci(mv),
+                        // the null move, as there is no legal move to play
+                        None => String::from("0000"),
+                    };
+                    let _ = send(&output, &format!("bestmove {}", best));
+                }));
+            }
+            "stop" => finish_search(&mut searching, &stop),
+            "quit" => break,
+            _ => {} // the protocol asks for anything unknown to be ignored
+        }
+    }
+    finish_search(&mut searching, &stop);
+    Ok(())
+}
@@ -0,0 +1,1288 @@
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::sync::mpsc::{channel, Receiver};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crate::{
+        bitboard::Bitboards,
+        board::BoardState,
+        chess960,
+        cli::{parse_args, play, CliOptions},
+        clock::{Bonus, Clock, TimeControl, TimeControls},
+        engine::{best_move, evaluate},
+        fen::{parse_fen, to_fen, to_shredder_fen, START_FEN},
+        game::{apply_move, redo_move, undo_move},
+        notation::{move_to_san, parse_move, parse_san},
+        perft::{divide, perft},
+        pgn::{export_pgn, import_pgn},
+        save::SavedGame,
+        types::{
+            Color, EngineSettings, GameMeta, GameOptions, GameResult, Hist, Move, MoveType, Piece,
+            PieceKind, Termination,
+        },
+        uci::{line_to_san, parse_bestmove, parse_info, UciEngine, UciInfo, UciLimit, UciScore},
+        uci_server::{parse_go, parse_position, serve},
+        utils::{piece_name, pretty_print_board, square_to_coord, BoardStyle},
+        zobrist::position_hash,
+        Game, Position,
+    };
+
+    #[test]
+    /// put all piece types in all squares and see if get moves doesn't panic
+    fn all_pieces_happy_in_all_squares() {
+        let board: BoardState = Default::default();
+        // dbg!(&board);
+        let piece_list = [
+            Piece::None,
+            Piece::Pawn(Color::Black, true),
+            Piece::Rook(Color::Black, true),
+            Piece::Bishop(Color::Black, true),
+            Piece::Knight(Color::Black, true),
+            Piece::King(Color::Black, true, false, false),
+            Piece::Queen(Color::Black, true),
+            Piece::Pawn(Color::White, true),
+            Piece::Rook(Color::White, true),
+            Piece::Bishop(Color::White, true),
+            Piece::Knight(Color::White, true),
+            Piece::King(Color::White, true, false, false),
+            Piece::Queen(Color::White, true),
+        ];
+        for piece in piece_list {
+            for col in 0..8 {
+                for row in 0..8 {
+                    println!("{:?}", piece);
+                    piece.get_moves((col, row), &board);
+                }
+            }
+        }
+    }
+
+    #[test]
+    /// the starting FEN gives the same board as a new game, and writes back out unchanged
+    fn fen_start_position_round_trip() {
+        let (board, meta) = parse_fen(START_FEN).expect("valid FEN");
+        assert_eq!(board, BoardState::new());
+        assert_eq!(meta.turn, 0);
+        assert_eq!(to_fen(&board, &meta), START_FEN);
+    }
+
+    #[test]
+    /// castling rights, en passant, clocks and side to move all survive a round trip
+    fn fen_round_trip() {
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2",
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/8/8/8/8/8/8/R3K2R b Kq - 12 40",
+        ] {
+            let (board, meta) = parse_fen(fen).expect("valid FEN");
+            assert_eq!(to_fen(&board, &meta), fen);
+        }
+    }
+
+    #[test]
+    /// broken FEN strings are rejected with an error rather than a panic
+    fn fen_rejects_invalid() {
+        for fen in [
+            "",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNRR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQXBNR w KQkq - 0 1",
+            "rnbq1bnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQ - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN1 w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e9 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e6 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - x 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 0",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 18446744073709551615",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 9223372036854775809",
+            "4k3/8/8/8/8/8/4R3/4K3 w - - 0 1",
+        ] {
+            assert!(parse_fen(fen).is_err(), "{fen} should be rejected");
+        }
+    }
+
+    #[test]
+    /// SAN adds a file or rank when two pieces of the same type can reach the target
+    fn san_disambiguation() {
+        let (board, meta) = parse_fen("4k3/8/8/8/8/5N2/8/RN2K2R w - - 0 1").expect("valid FEN");
+        assert_eq!(
+            move_to_san(
+                Move {
+                    from: (1, 0),
+                    to: (3, 1),
+                    kind: MoveType::Move,
+                    promotion: None
+                },
+                &board,
+                &meta
+            ),
+            "Nbd2"
+        );
+        assert_eq!(
+            move_to_san(
+                Move {
+                    from: (0, 0),
+                    to: (0, 5),
+                    kind: MoveType::Move,
+                    promotion: None
+                },
+                &board,
+                &meta
+            ),
+            "Ra6"
+        );
+        assert_eq!(parse_san("Nfd2", &board, &meta).unwrap().from, (5, 2));
+        assert!(parse_san("Nd2", &board, &meta).is_err());
+        assert!(parse_san("Nd3", &board, &meta).is_err());
+        assert!(parse_san("Z9", &board, &meta).is_err());
+    }
+
+    #[test]
+    /// a game with castling, disambiguation, checks and mate writes back out as it was read
+    fn pgn_round_trip() {
+        let movetext =
+            "1. e4 e5 2. Nf3 d6 3. d4 Bg4 4. dxe5 Bxf3 5. Qxf3 dxe5 6. Bc4 Nf6 7. Qb3 Qe7 8.\n\
+            Nc3 c6 9. Bg5 b5 10. Nxb5 cxb5 11. Bxb5+ Nbd7 12. O-O-O Rd8 13. Rxd7 Rxd7 14.\n\
+            Rd1 Qe6 15. Bxd7+ Nxd7 16. Qb8+ Nxb8 17. Rd8# 1-0\n";
+        let (_, meta, history) = import_pgn(movetext).expect("valid PGN");
+        assert_eq!(meta.result, GameResult::WhiteWins(Termination::Checkmate));
+        let pgn = export_pgn(&history, &meta, None);
+        assert!(pgn.contains("[Result \"1-0\"]"));
+        assert!(pgn.ends_with(movetext), "{pgn}");
+    }
+
+    #[test]
+    /// en passant and promotion are replayed, and a FEN tag sets the starting position
+    fn pgn_special_moves() {
+        let (board, meta, history) = import_pgn(
+            "1. e4 Nf6 2. e5 d5 3. exd6 {en passant} e6 4. dxc7 Qe7 (4... Qd7) 5. cxb8=Q *",
+        )
+        .expect("valid PGN");
+        assert_eq!(board.get((1, 7)), Piece::Queen(Color::White, false));
+        assert_eq!(history.moves[4].san, "exd6");
+        assert_eq!(history.moves[8].san, "cxb8=Q");
+        assert_eq!(meta.turn, 9);
+
+        let (_, _, history) =
+            import_pgn("[FEN \"4k3/8/8/8/8/8/8/R3K3 b Q - 0 30\"]\n\n30... Kd7 31. O-O-O+ *")
+                .expect("valid PGN");
+        assert_eq!(history.moves[1].san, "O-O-O+");
+    }
+
+    #[test]
+    /// an illegal move in a PGN is reported with its move number and token
+    fn pgn_reports_bad_move() {
+        let error = import_pgn("1. e4 e5 2. Nf3 Nc6 3. Bb5 Nd4 4. Bxe9 *").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "move 4. 'Bxe9': 'Bxe9' is not valid notation"
+        );
+        let error = import_pgn("1. e4 e5 2. Ke3 *").unwrap_err();
+        assert!(error.to_string().starts_with("move 2. 'Ke3'"));
+        let error = import_pgn("1. e4 e5 2. Nf3 Nf3 *").unwrap_err();
+        assert!(error.to_string().starts_with("move 2... 'Nf3'"));
+    }
+
+    #[test]
+    /// typed moves are understood in SAN, UCI and hyphenated long algebraic form
+    fn parse_typed_moves() {
+        let (board, meta) = parse_fen("r3k3/1P6/8/8/8/8/8/R3K1NR w KQq - 0 1").expect("valid FEN");
+        let white_king = (4, 0);
+        for (text, expected) in [
+            ("O-O-O", (white_king, (2, 0), MoveType::Castle, None)),
+            ("e1c1", (white_king, (2, 0), MoveType::Castle, None)),
+            ("Ng1-f3", ((6, 0), (5, 2), MoveType::Move, None)),
+            ("g1h3", ((6, 0), (7, 2), MoveType::Move, None)),
+            (
+                "b7a8n",
+                ((1, 6), (0, 7), MoveType::Capture, Some(PieceKind::Knight)),
+            ),
+            (
+                "bxa8=Q+",
+                ((1, 6), (0, 7), MoveType::Capture, Some(PieceKind::Queen)),
+            ),
+        ] {
+            let mv = parse_move(text, &board, &meta).unwrap();
+            assert_eq!((mv.from, mv.to, mv.kind, mv.promotion), expected, "{text}");
+        }
+        for text in [
+            "O-O", "e1g1", "b7b8", "b7a8", "e1e3", "e2e4", "g1f3q", "i1i2", "Nf9",
+        ] {
+            assert!(parse_move(text, &board, &meta).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    /// running out of moves without being in check ends the game in a draw
+    fn stalemate_is_a_draw() {
+        let (_, meta) = parse_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").expect("valid FEN");
+        assert_eq!(meta.result, GameResult::Draw(Termination::Stalemate));
+
+        let (_, meta, history) =
+            import_pgn("[FEN \"k7/8/8/2Q5/8/8/8/7K w - - 0 1\"]\n\n1. Qb6 *").expect("valid PGN");
+        assert_eq!(meta.result, GameResult::Draw(Termination::Stalemate));
+        assert!(export_pgn(&history, &meta, None).ends_with("1. Qb6 1/2-1/2\n"));
+        assert!(import_pgn("[FEN \"k7/8/8/2Q5/8/8/8/7K w - - 0 1\"]\n\n1. Qb6 Ka7 *").is_err());
+    }
+
+    #[test]
+    /// the same position reached by different routes hashes the same, unusable en passant included
+    fn position_hash_matches_fen() {
+        let (board, meta, _) = import_pgn("1. Nf3 Nc6 2. e4 e5 *").expect("valid PGN");
+        let (fen_board, fen_meta) =
+            parse_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 0 3")
+                .expect("valid FEN");
+        assert_eq!(
+            position_hash(&board, &meta),
+            position_hash(&fen_board, &fen_meta)
+        );
+
+        let (board, meta, _) = import_pgn("1. e4 *").expect("valid PGN");
+        let (fen_board, fen_meta) =
+            parse_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1")
+                .expect("valid FEN");
+        assert_eq!(
+            position_hash(&board, &meta),
+            position_hash(&fen_board, &fen_meta)
+        );
+        let (board, meta) = parse_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").expect("valid FEN");
+        let (other_board, other_meta) =
+            parse_fen("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1").expect("valid FEN");
+        assert_ne!(
+            position_hash(&board, &meta),
+            position_hash(&other_board, &other_meta)
+        );
+    }
+
+    #[test]
+    /// a third repetition can be claimed as a draw, a fifth ends the game
+    fn repetition_draws() {
+        let shuffle = "Nf3 Nf6 Ng1 Ng8 ";
+        let (_, meta, _) = import_pgn(shuffle).expect("valid PGN");
+        assert_eq!(meta.repetitions, 2);
+        assert_eq!(meta.claimable_draw, None);
+        let (_, meta, _) = import_pgn(&shuffle.repeat(2)).expect("valid PGN");
+        assert_eq!(meta.repetitions, 3);
+        assert_eq!(meta.claimable_draw, Some(Termination::ThreefoldRepetition));
+        assert_eq!(meta.result, GameResult::Ongoing);
+        let (_, meta, _) = import_pgn(&format!("{}Nc3", shuffle.repeat(2))).expect("valid PGN");
+        assert_eq!(meta.claimable_draw, None);
+        let (_, meta, history) = import_pgn(&shuffle.repeat(4)).expect("valid PGN");
+        assert_eq!(meta.repetitions, 5);
+        assert_eq!(
+            meta.result,
+            GameResult::Draw(Termination::FivefoldRepetition)
+        );
+        assert!(export_pgn(&history, &meta, None).ends_with("1/2-1/2\n"));
+        assert!(import_pgn(&format!("{}Nf3", shuffle.repeat(4))).is_err());
+    }
+
+    #[test]
+    /// the halfmove clock counts quiet moves, allowing a draw claim at 50 moves and ending the game at 75
+    fn fifty_and_seventy_five_move_rules() {
+        let rook_endgame = |clock: usize, moves: &str| {
+            let fen = format!("4k3/4p3/8/8/8/8/8/R3K3 w - - {clock} 81");
+            import_pgn(&format!("[FEN \"{fen}\"]\n\n{moves} *")).expect("valid PGN")
+        };
+        let (board, meta, _) = rook_endgame(98, "81. Ra2 Kd8");
+        assert_eq!(meta.halfmove_clock, 100);
+        assert_eq!(meta.claimable_draw, Some(Termination::FiftyMoveRule));
+        assert!(to_fen(&board, &meta).ends_with(" 100 82"));
+        let (_, meta, _) = rook_endgame(98, "81. Ra2 e5");
+        assert_eq!(meta.halfmove_clock, 0);
+        assert_eq!(meta.claimable_draw, None);
+        let (_, meta, _) = rook_endgame(147, "81. Ra7 Kd8 82. Rxe7");
+        assert_eq!(meta.halfmove_clock, 0);
+        let (_, meta, _) = rook_endgame(148, "81. Ra2 Kd8");
+        assert_eq!(
+            meta.result,
+            GameResult::Draw(Termination::SeventyFiveMoveRule)
+        );
+        // mate on the last allowed move still wins
+        let (_, meta) = parse_fen("k7/8/1K6/8/8/8/8/7R w - - 149 120").expect("valid FEN");
+        assert_eq!(meta.result, GameResult::Ongoing);
+        let (_, meta, _) = import_pgn("[FEN \"k7/8/1K6/8/8/8/8/7R w - - 149 120\"]\n\n120. Rh8# *")
+            .expect("valid PGN");
+        assert_eq!(meta.result, GameResult::WhiteWins(Termination::Checkmate));
+
+        let (_, meta) = parse_fen("4k3/8/8/8/8/8/8/R3K3 b - - 150 80").expect("valid FEN");
+        assert_eq!(
+            meta.result,
+            GameResult::Draw(Termination::SeventyFiveMoveRule)
+        );
+        let meta: GameMeta = serde_json::from_str(&serde_json::to_string(&meta).unwrap()).unwrap();
+        assert_eq!(meta.halfmove_clock, 150);
+    }
+
+    #[test]
+    /// positions where nobody can mate are drawn, and running out of time against a lone king is too
+    fn insufficient_material() {
+        for fen in [
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/2B1K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/1N2K3 b - - 0 1",
+            "4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/B1B1K1B1 w - - 0 1",
+        ] {
+            let (board, meta) = parse_fen(fen).expect("valid FEN");
+            assert!(board.insufficient_material(), "{fen}");
+            assert_eq!(
+                meta.result,
+                GameResult::Draw(Termination::InsufficientMaterial),
+                "{fen}"
+            );
+        }
+        for fen in [
+            "2b1k3/8/8/8/8/8/8/2B1K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/1NB1K3 w - - 0 1",
+            "4k1n1/8/8/8/8/8/8/1N2K3 w - - 0 1",
+            "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+        ] {
+            let (board, meta) = parse_fen(fen).expect("valid FEN");
+            assert!(!board.insufficient_material(), "{fen}");
+            assert_eq!(meta.result, GameResult::Ongoing, "{fen}");
+        }
+
+        // capturing the last pawn ends the game
+        let (_, meta, _) = import_pgn("[FEN \"4k3/8/8/8/8/8/4p3/4KB2 w - - 0 1\"]\n\n1. Bxe2 *")
+            .expect("valid PGN");
+        assert_eq!(
+            meta.result,
+            GameResult::Draw(Termination::InsufficientMaterial)
+        );
+
+        let (board, mut meta) = parse_fen("4k3/8/8/8/8/8/8/1N2K2R b - - 0 1").expect("valid FEN");
+        assert!(!board.lone_king(Color::White));
+        assert!(board.lone_king(Color::Black));
+        meta.time_out(Color::Black, &board);
+        assert_eq!(meta.result, GameResult::WhiteWins(Termination::Timeout));
+        let (board, mut meta) = parse_fen("4k3/8/8/8/8/8/8/1N2K2R w - - 0 1").expect("valid FEN");
+        meta.time_out(Color::White, &board);
+        assert_eq!(
+            meta.result,
+            GameResult::Draw(Termination::TimeoutVsInsufficientMaterial)
+        );
+        // a lone bishop can still mate a king boxed in by its own pawns, so flagging loses
+        let fen = "7k/6pp/8/8/8/8/8/2B1K3 b - - 0 1";
+        let (board, mut meta) = parse_fen(fen).expect("valid FEN");
+        meta.time_out(Color::Black, &board);
+        assert_eq!(meta.result, GameResult::WhiteWins(Termination::Timeout));
+        let (board, mut meta) = parse_fen(&fen.replace("2B1K3", "4K3")).expect("valid FEN");
+        meta.time_out(Color::Black, &board);
+        assert_eq!(
+            meta.result,
+            GameResult::Draw(Termination::TimeoutVsInsufficientMaterial)
+        );
+    }
+
+    #[test]
+    /// taking moves back restores everything exactly, including castling, en passant and promotion
+    fn undo_and_redo() {
+        let moves = "e4 d5 e5 f5 exf6 Nc6 fxg7 Qd6 gxh8=Q Bd7 Nf3 O-O-O Be2 Kb8 O-O";
+        let mut board = BoardState::new();
+        let mut meta = GameMeta::default();
+        let mut history = Hist::default();
+        let mut snapshots = Vec::new();
+        for san in moves.split(' ') {
+            snapshots.push((
+                board.clone(),
+                meta,
+                history.moves.clone(),
+                history.score.clone(),
+            ));
+            let mv = parse_san(san, &board, &meta).expect("legal move");
+            apply_move(&mut board, &mut meta, &mut history, mv);
+        }
+        let end = (
+            board.clone(),
+            meta,
+            history.moves.clone(),
+            history.score.clone(),
+        );
+        while let Some((old_board, old_meta, old_moves, old_score)) = snapshots.pop() {
+            assert!(undo_move(&mut board, &mut meta, &mut history));
+            assert_eq!(board, old_board);
+            assert_eq!(meta, old_meta);
+            assert_eq!(history.moves, old_moves);
+            assert_eq!(history.score, old_score);
+        }
+        assert!(!undo_move(&mut board, &mut meta, &mut history));
+        assert_eq!(history.hashes.len(), 1);
+        while redo_move(&mut board, &mut meta, &mut history) {}
+        assert_eq!(board, end.0);
+        assert_eq!(meta, end.1);
+        assert_eq!(history.moves, end.2);
+
+        // a new move clears the moves that could have been redone
+        assert!(undo_move(&mut board, &mut meta, &mut history));
+        let mv = parse_san("Kf1", &board, &meta).expect("legal move");
+        apply_move(&mut board, &mut meta, &mut history, mv);
+        assert!(!redo_move(&mut board, &mut meta, &mut history));
+
+        // an under-promotion is taken back to the pawn, and redone to the same piece
+        let (mut board, mut meta) =
+            parse_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").expect("valid FEN");
+        let before = (board.clone(), meta);
+        let mut history = Hist::default();
+        let mv = Move {
+            from: (1, 6),
+            to: (1, 7),
+            kind: MoveType::Move,
+            promotion: Some(PieceKind::Knight),
+        };
+        apply_move(&mut board, &mut meta, &mut history, mv);
+        assert_eq!(board.get((1, 7)), Piece::Knight(Color::White, false));
+        assert_eq!(history.moves[0].san, "b8=N");
+        assert!(undo_move(&mut board, &mut meta, &mut history));
+        assert_eq!((board.clone(), meta), before);
+        assert!(redo_move(&mut board, &mut meta, &mut history));
+        assert_eq!(board.get((1, 7)), Piece::Knight(Color::White, false));
+    }
+
+    #[test]
+    /// the computer finds a mate in one, takes free material and plays something in the opening
+    fn engine_chooses_good_moves() {
+        assert_eq!(evaluate(&Position::new()), 0);
+        let depth = |depth| EngineSettings {
+            depth,
+            ..Default::default()
+        };
+
+        let position = Position::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").expect("valid FEN");
+        let mv = best_move(&position, &depth(2)).expect("a move");
+        assert_eq!((mv.from, mv.to), ((0, 0), (0, 7)));
+
+        let position = Position::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").expect("valid FEN");
+        let mv = best_move(&position, &depth(2)).expect("a move");
+        assert_eq!((mv.from, mv.to), ((3, 1), (3, 4)));
+
+        // promote to a queen rather than anything less
+        let position = Position::from_fen("8/1P5k/8/8/8/8/8/4K3 w - - 0 1").expect("valid FEN");
+        let mv = best_move(&position, &depth(1)).expect("a move");
+        assert_eq!(mv.promotion, Some(PieceKind::Queen));
+
+        assert!(best_move(&Position::new(), &depth(2)).is_some());
+        let position = Position::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").expect("valid FEN");
+        assert!(best_move(&position, &depth(2)).is_none());
+    }
+
+    #[test]
+    /// weaker settings still play legal moves, and the search stops when its budget runs out
+    fn engine_strength_settings() {
+        let position = Position::new();
+        let legal = position.legal_moves();
+        for skill_level in [0, 5, 10] {
+            let settings = EngineSettings {
+                depth: 2,
+                skill_level,
+                ..Default::default()
+            };
+            let mv = best_move(&position, &settings).expect("a move");
+            assert!(legal.contains(&mv));
+        }
+        // the top skill level always plays the same move, lower levels vary
+        let distinct_moves = |skill_level| {
+            let settings = EngineSettings {
+                depth: 1,
+                skill_level,
+                ..Default::default()
+            };
+            let mut moves: Vec<_> = (0..20)
+                .filter_map(|_| best_move(&position, &settings))
+                .collect();
+            moves.sort_by_key(|mv| (mv.from, mv.to));
+            moves.dedup();
+            moves.len()
+        };
+        assert_eq!(distinct_moves(20), 1);
+        assert!(distinct_moves(0) > 1);
+
+        let position = Position::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").expect("valid FEN");
+        for settings in [
+            EngineSettings {
+                depth: 20,
+                node_limit: Some(500),
+                move_time: None,
+                ..Default::default()
+            },
+            EngineSettings {
+                depth: 20,
+                node_limit: None,
+                move_time: Some(100),
+                ..Default::default()
+            },
+        ] {
+            let start = std::time::Instant::now();
+            assert!(best_move(&position, &settings).is_some());
+            assert!(start.elapsed() < std::time::Duration::from_secs(5));
+        }
+    }
+
+    /// Start a stand-in engine on its own thread, which answers every search with the same
+    /// script, and pass back every command it receives
+    ///
+    /// Searches to depth 40 or for a time only end when the engine is told to stop.
+    fn scripted_engine() -> (UciEngine, Receiver<String>) {
+        let (engine_input, our_input) = std::io::pipe().expect("pipe");
+        let (our_output, mut engine_output) = std::io::pipe().expect("pipe");
+        let (sender, commands) = channel();
+        thread::spawn(move || {
+            for command in BufReader::new(engine_input)
+                .lines()
+                .map_while(|line| line.ok())
+            {
+                let endless = command == "go depth 40" || command.starts_with("go movetime");
+                let reply = match command.split_whitespace().next() {
+                    Some("go") if endless => "info depth 1 score cp 5 pv d2d4\n",
+                    Some("stop") => "bestmove d2d4\n",
+                    Some("uci") => "id name Scripted Engine\nid author Nobody\nuciok\n",
+                    Some("isready") => "readyok\n",
+                    Some("go") => concat!(
+                        "info string thinking\n",
+                        "info depth 1 seldepth 1 score cp 20 nodes 20 pv e2e4\n",
+                        "info depth 2 score mate 3 pv e2e4 e7e5 d1h5\n",
+                        "bestmove e2e4 ponder e7e5\n",
+                    ),
+                    _ => "",
+                };
+                let quit = command == "quit";
+                let _ = sender.send(command);
+                if engine_output.write_all(reply.as_bytes()).is_err() || quit {
+                    break;
+                }
+            }
+        });
+        let engine = UciEngine::from_streams(our_output, our_input).expect("handshake");
+        (engine, commands)
+    }
+
+    #[test]
+    /// the UCI client reads engine output and drives the handshake and search
+    fn uci_client() {
+        assert_eq!(
+            parse_info("info depth 12 seldepth 20 multipv 1 score cp -35 lowerbound nodes 1000 pv g1f3 d7d5"),
+            Some(UciInfo {
+                depth: Some(12),
+                score: Some(UciScore::Centipawns(-35)),
+                pv: vec!["g1f3".to_string(), "d7d5".to_string()],
+            })
+        );
+        assert_eq!(
+            parse_info("info score mate -2 depth 5").and_then(|info| info.score),
+            Some(UciScore::Mate(-2))
+        );
+        assert_eq!(parse_info("bestmove e2e4"), None);
+        assert_eq!(
+            parse_bestmove("bestmove e7e8q ponder a1a2"),
+            Some("e7e8q".to_string())
+        );
+        assert_eq!(parse_bestmove("info depth 1"), None);
+
+        let (board, meta) = parse_fen(START_FEN).expect("valid FEN");
+        let line = [
+            "e2e4", "e7e5", "d1h5", "b8c6", "f1c4", "g8f6", "h5f7", "e8f7",
+        ];
+        let line: Vec<String> = line.iter().map(|text| text.to_string()).collect();
+        assert_eq!(
+            line_to_san(&line, &board, &meta),
+            ["e4", "e5", "Qh5", "Nc6", "Bc4", "Nf6", "Qxf7#"]
+        );
+
+        let (mut engine, commands) = scripted_engine();
+        assert_eq!(engine.name, "Scripted Engine");
+        let (_, _, history) = import_pgn("1. e4 e5 2. Nf3 *").expect("valid PGN");
+        engine.set_position(&history, false).expect("position");
+        let mut reports = Vec::new();
+        let best = engine
+            .go(UciLimit::Depth(2), |info| {
+                reports.push(info);
+                Ok(())
+            })
+            .expect("search");
+        assert_eq!(best, "e2e4");
+        assert_eq!(reports.len(), 3);
+        assert_eq!(reports[2].score, Some(UciScore::Mate(3)));
+        drop(engine);
+        let sent: Vec<String> = commands.iter().collect();
+        assert_eq!(
+            sent,
+            [
+                "uci",
+                "isready",
+                "position startpos moves e2e4 e7e5 g1f3",
+                "go depth 2",
+                "quit"
+            ]
+        );
+    }
+
+    #[test]
+    /// a search is stopped from another thread while the engine is busy, or once its time is up
+    fn uci_client_stops_search() {
+        let (mut engine, commands) = scripted_engine();
+        let stopper = engine.stopper();
+        let stopping = thread::spawn(move || {
+            thread::sleep(std::time::Duration::from_millis(50));
+            stopper.stop()
+        });
+        let start = std::time::Instant::now();
+        let best = engine.go(UciLimit::Depth(40), |_| Ok(())).expect("search");
+        assert_eq!(best, "d2d4");
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+        stopping.join().unwrap().expect("stop");
+
+        let best = engine
+            .go(UciLimit::MoveTime(20), |_| Ok(()))
+            .expect("search");
+        assert_eq!(best, "d2d4");
+        // a search given up on is stopped, so its best move isn't read as the next one's
+        let error = engine
+            .go(UciLimit::Depth(40), |_| {
+                Err(anyhow::anyhow!("nobody is listening"))
+            })
+            .expect_err("search given up");
+        assert_eq!(error.to_string(), "nobody is listening");
+        let best = engine.go(UciLimit::Depth(2), |_| Ok(())).expect("search");
+        assert_eq!(best, "e2e4");
+        drop(engine);
+        let sent: Vec<String> = commands.iter().collect();
+        assert_eq!(
+            sent,
+            [
+                "uci",
+                "isready",
+                "go depth 40",
+                "stop",
+                "go movetime 20",
+                "stop",
+                "go depth 40",
+                "stop",
+                "go depth 2",
+                "quit"
+            ]
+        );
+    }
+
+    #[test]
+    /// the UCI server sets up positions, shares out the clock and answers a search with its best move
+    fn uci_server() {
+        let position =
+            parse_position("startpos moves e2e4 e7e5 g1f3", false).expect("valid position");
+        let expected =
+            Position::from_fen("rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2")
+                .expect("valid FEN");
+        assert_eq!(position, expected);
+        let position = parse_position("fen 4k3/1P6/8/8/8/8/8/4K3 w - - 0 1 moves b7b8q", false)
+            .expect("valid position");
+        let meta = *position.meta();
+        assert_eq!(meta.turn, 1);
+        assert!(parse_position("startpos moves e2e5", false).is_err());
+        assert!(parse_position("nowhere", false).is_err());
+        // with UCI_Chess960 on, castling is the king taking its own rook
+        let fen = "fen r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1 moves";
+        assert!(parse_position(&format!("{fen} e1h1"), true).is_ok());
+        assert!(parse_position(&format!("{fen} e1g1"), true).is_err());
+
+        let settings = parse_go("wtime 60000 btime 1000 winc 2000 binc 0", &meta);
+        assert_eq!(settings.move_time, Some(1000 / 30));
+        let settings = parse_go("wtime 60000 btime 30000 movestogo 10", &GameMeta::default());
+        assert_eq!(settings.move_time, Some(6000));
+        let settings = parse_go("depth 4 movetime 200", &meta);
+        assert_eq!((settings.depth, settings.move_time), (4, Some(200)));
+        assert_eq!(parse_go("infinite", &meta).move_time, None);
+
+        let (server_input, mut gui_output) = std::io::pipe().expect("pipe");
+        let (gui_input, server_output) = std::io::pipe().expect("pipe");
+        let server = thread::spawn(move || serve(BufReader::new(server_input), server_output));
+        let mut replies = BufReader::new(gui_input)
+            .lines()
+            .map_while(|line| line.ok());
+        let mut command = |text: &str| writeln!(gui_output, "{text}").expect("send command");
+        command("uci");
+        assert!(replies.next().expect("reply").starts_with("id name"));
+        assert!(replies.by_ref().any(|line| line == "uciok"));
+        command("isready");
+        assert_eq!(replies.next().as_deref(), Some("readyok"));
+        command("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        command("go depth 3");
+        let lines: Vec<String> = replies
+            .by_ref()
+            .take_while(|line| !line.starts_with("bestmove"))
+            .collect();
+        assert!(
+            lines.iter().any(|line| line.contains("score mate 1")),
+            "{lines:?}"
+        );
+        // a side that is already mated answers with the null move
+        command("position fen R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1");
+        command("go depth 3");
+        assert_eq!(
+            replies.find(|line| line.starts_with("bestmove")).as_deref(),
+            Some("bestmove 0000")
+        );
+        command("position startpos moves e2e4");
+        command("go perft 2");
+        let lines: Vec<String> = replies
+            .by_ref()
+            .take_while(|line| !line.starts_with("Nodes searched"))
+            .collect();
+        assert!(lines.contains(&String::from("e7e5: 29")), "{lines:?}");
+        assert_eq!(lines.len(), 21); // 20 replies and a blank line
+        command("go infinite");
+        command("stop");
+        assert!(replies.any(|line| line.starts_with("bestmove")));
+        command("quit");
+        server.join().expect("server thread").expect("clean exit");
+    }
+
+    /// Standard perft positions with their known node counts by depth
+    /// <https://www.chessprogramming.org/Perft_Results>
+    const PERFT_POSITIONS: [(&str, &[u64]); 6] = [
+        (START_FEN, &[20, 400, 8902, 197281]),
+        (
+            // "Kiwipete", full of castling, en passant, promotions and pins
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            &[48, 2039, 97862],
+        ),
+        (
+            // en passant that would expose the king along the rank
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            &[14, 191, 2812, 43238],
+        ),
+        (
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            &[6, 264, 9467],
+        ),
+        (
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            &[44, 1486, 62379],
+        ),
+        (
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+            &[46, 2079, 89890],
+        ),
+    ];
+
+    #[test]
+    /// the legal move generator reaches exactly the known number of positions
+    fn perft_node_counts() {
+        for (fen, counts) in PERFT_POSITIONS {
+            let mut position = Position::from_fen(fen).expect("valid FEN");
+            for (depth, expected) in (1..).zip(counts) {
+                assert_eq!(
+                    perft(&mut position, depth),
+                    *expected,
+                    "{} at depth {}",
+                    fen,
+                    depth
+                );
+            }
+            // every move made along the way has been unmade again
+            assert_eq!(position, Position::from_fen(fen).expect("valid FEN"));
+        }
+    }
+
+    #[test]
+    /// bitboards convert back to the same board, and agree on which squares are attacked
+    fn bitboard_conversion() {
+        for (fen, _) in PERFT_POSITIONS {
+            let (board, meta) = parse_fen(fen).expect("valid FEN");
+            let bitboards = Bitboards::new(&board, &meta);
+            let mut round_trip = BoardState::from(&bitboards);
+            // check and mate are tracked by the game metadata, not the bitboards
+            for king in [meta.white_king, meta.black_king] {
+                round_trip.set(king.square, king.piece);
+            }
+            assert_eq!(round_trip, board, "{}", fen);
+        }
+        let (board, _) = parse_fen(PERFT_POSITIONS[1].0).expect("valid FEN");
+        let bitboards = Bitboards::from(&board);
+        // the knight on e5 covers d7 and f7, the pawn on b4 covers a3 and c3
+        assert!(bitboards.attacked((3, 6), Color::White));
+        assert!(bitboards.attacked((5, 6), Color::White));
+        assert!(bitboards.attacked((2, 2), Color::Black));
+        assert!(!bitboards.attacked((1, 2), Color::Black));
+        // the queen on e7 is blocked by the pawn on e6
+        assert!(!bitboards.attacked((4, 4), Color::Black));
+    }
+
+    #[test]
+    /// every Chess960 array is numbered, with the bishops on opposite colours and the king between the rooks
+    fn chess960_start_positions() {
+        let rank = |index| -> String {
+            chess960::back_rank(index)
+                .expect("valid index")
+                .iter()
+                .collect()
+        };
+        assert_eq!(rank(chess960::STANDARD), "RNBQKBNR");
+        assert_eq!(rank(0), "BBQNNRKR");
+        assert_eq!(rank(959), "RKRNNQBB");
+        assert!(chess960::back_rank(960).is_err());
+        let mut seen = std::collections::HashSet::new();
+        for index in 0..chess960::POSITIONS {
+            let rank = rank(index);
+            let files = |piece| {
+                rank.match_indices(piece)
+                    .map(|(file, _)| file)
+                    .collect::<Vec<_>>()
+            };
+            let (bishops, rooks, king) = (files('B'), files('R'), files('K'));
+            assert_eq!(bishops.len(), 2);
+            assert_ne!(bishops[0] % 2, bishops[1] % 2, "{}", rank);
+            assert!(rooks[0] < king[0] && king[0] < rooks[1], "{}", rank);
+            assert!(seen.insert(rank));
+        }
+        assert!(chess960::random_index() < chess960::POSITIONS);
+
+        let position = Position::chess960(chess960::STANDARD).expect("valid index");
+        assert!(position.is_chess960());
+        assert_eq!(position.fen(), START_FEN);
+        assert_eq!(
+            position.shredder_fen(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w HAha - 0 1"
+        );
+    }
+
+    #[test]
+    /// Chess960 castling puts the king and rook on their usual squares, from wherever they started
+    fn chess960_castling() {
+        // king on g1 between rooks on f1 and h1, in X-FEN and Shredder-FEN
+        let fen = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9";
+        let mut position = Position::from_fen(fen).expect("valid FEN");
+        assert!(position.is_chess960());
+        assert_eq!(position.shredder_fen(), fen);
+        assert_eq!(position.fen(), fen.replace("HFhf", "KQkq"));
+        for (depth, expected) in (1..).zip([21, 528, 12189]) {
+            assert_eq!(perft(&mut position, depth), expected, "depth {}", depth);
+        }
+        // a rook further out than the castling rook needs the file in X-FEN too
+        let (board, meta) = parse_fen("4k3/8/8/8/8/8/8/R1R1K3 w C - 0 1").expect("valid FEN");
+        assert_eq!(to_fen(&board, &meta), "4k3/8/8/8/8/8/8/R1R1K3 w C - 0 1");
+        assert_eq!(
+            to_shredder_fen(&board, &meta),
+            "4k3/8/8/8/8/8/8/R1R1K3 w C - 0 1"
+        );
+        assert!(parse_fen("4k3/8/8/8/8/8/8/R1R1K3 w D - 0 1").is_err());
+
+        // white's king and rook swap places, black's king steps over to c8 with the rook beside it
+        let start = "rk2r3/8/8/8/8/8/8/R4KR1 w GAea - 0 1";
+        let mut game = Game::from_fen(start).expect("valid FEN");
+        assert_eq!(game.position().fen(), start.replace("GAea", "KQkq"));
+        game.play((5, 0), (6, 0), None).expect("legal castling");
+        assert_eq!(game.history().moves[0].san, "O-O");
+        assert_eq!(
+            game.position().board().get((6, 0)),
+            Piece::King(Color::White, false, false, false)
+        );
+        assert_eq!(
+            game.position().board().get((5, 0)),
+            Piece::Rook(Color::White, false)
+        );
+        game.play_notation("O-O-O").expect("legal castling");
+        assert!(game.position().board().get((2, 7)).is_king(Color::Black));
+        assert_eq!(
+            game.position().board().get((3, 7)),
+            Piece::Rook(Color::Black, false)
+        );
+        assert_eq!(game.history().moves[1].san, "O-O-O");
+        assert!(game.pgn().contains("[Variant \"Chess960\"]"));
+        let replayed = Game::from_pgn(&game.pgn()).expect("valid PGN");
+        assert_eq!(replayed.position(), game.position());
+        assert!(game.undo() && game.undo());
+        assert_eq!(game.position().shredder_fen(), start);
+    }
+
+    #[test]
+    /// time controls are read and written in the PGN TimeControl form
+    fn time_control_notation() {
+        for text in [
+            "40/7200:3600",
+            "300+2",
+            "40/7200+30:1800+30",
+            "300d5",
+            "180b2",
+            "0+1",
+            "90.5",
+        ] {
+            let control: TimeControl = text.parse().expect("valid time control");
+            assert_eq!(control.to_string(), text);
+        }
+        let control: TimeControl = "40/5400+30:1800+30".parse().expect("valid time control");
+        assert_eq!(control.stages.len(), 2);
+        assert_eq!(control.stages[0].moves, Some(40));
+        assert_eq!(control.stages[0].time, 5_400_000);
+        assert_eq!(control.stages[1].bonus, Bonus::Increment(30_000));
+        for text in ["", "abc", "0/60", "40/", "60+x", "0"] {
+            assert!(text.parse::<TimeControl>().is_err(), "{}", text);
+        }
+        assert_eq!(
+            serde_json::to_string(&control).expect("serialize"),
+            "\"40/5400+30:1800+30\""
+        );
+    }
+
+    #[test]
+    /// the clock counts down the player to move, with increments, delays and stages
+    fn clock_timing() {
+        let start = Instant::now();
+        let at = |seconds: u64| start + Duration::from_secs(seconds);
+        let clock_for = |text: &str| {
+            let mut clock = Clock::new(TimeControls::both(
+                text.parse().expect("valid time control"),
+            ));
+            clock.start(Color::White, start);
+            clock
+        };
+
+        // 2 moves in a minute, then 30 seconds more with a 5 second increment
+        let mut clock = clock_for("2/60:30+5");
+        assert_eq!(clock.press(at(10)), Some(50_000));
+        assert_eq!(clock.remaining(Color::Black, at(12)), 58_000);
+        assert_eq!(clock.press(at(15)), Some(55_000));
+        assert_eq!(clock.press(at(35)), Some(60_000));
+        assert_eq!(clock.press(at(40)), Some(80_000));
+        assert_eq!(clock.press(at(50)), Some(55_000));
+        assert_eq!(clock.running(), Some(Color::Black));
+
+        // a simple delay holds the clock back, a Bronstein delay gives the time back afterwards
+        let mut clock = clock_for("60d5");
+        assert_eq!(clock.remaining(Color::White, at(3)), 60_000);
+        assert_eq!(clock.press(at(3)), Some(60_000));
+        assert_eq!(clock.press(at(11)), Some(57_000));
+        let mut clock = clock_for("60b5");
+        assert_eq!(clock.remaining(Color::White, at(3)), 57_000);
+        assert_eq!(clock.press(at(3)), Some(60_000));
+        assert_eq!(clock.press(at(11)), Some(57_000));
+
+        // the flag falls once the time is up, and a stopped clock stays put
+        let mut clock = clock_for("10+1");
+        assert_eq!(clock.flagged(at(9)), None);
+        assert_eq!(clock.flagged(at(10)), Some(Color::White));
+        clock.stop(at(4));
+        assert_eq!(clock.reading(at(100)).white, 6_000);
+        assert_eq!(clock.reading(at(100)).running, None);
+
+        // a game ends when the flag falls, and records the time left after each move
+        let mut game = Game::new();
+        game.set_clock(Clock::new(TimeControls::both(
+            "300+2".parse().expect("valid"),
+        )));
+        game.play_notation("e4").expect("legal move");
+        assert!(game.history().moves[0]
+            .clock
+            .is_some_and(|millis| millis > 301_000));
+        assert_eq!(game.clock().and_then(Clock::running), Some(Color::Black));
+        let pgn = game.pgn();
+        assert!(pgn.contains("[TimeControl \"300+2\"]"));
+        assert!(pgn.contains("1. e4 {[%clk 0:05:0"));
+        let mut game = Game::new();
+        game.set_clock(Clock::new(TimeControls::both(
+            "0+1".parse().expect("valid"),
+        )));
+        assert_eq!(game.check_flag(), Some(Color::White));
+        assert_eq!(
+            game.position().result(),
+            GameResult::BlackWins(Termination::Timeout)
+        );
+        assert!(game.play_notation("e4").is_err());
+        assert_eq!(game.clock().and_then(Clock::running), None);
+    }
+
+    #[test]
+    /// against the computer, undo and redo go back and forth between the player's turns
+    fn undo_and_redo_turns() {
+        let mut game = Game::new();
+        for notation in ["e4", "e5", "Nf3", "Nc6"] {
+            game.play_notation(notation).expect("legal move");
+        }
+        let computer = Some(Color::Black);
+        assert!(game.undo_turn(computer));
+        assert_eq!(game.history().moves.len(), 2);
+        assert_eq!(game.position().turn(), Color::White);
+        assert!(game.redo_turn(computer));
+        assert_eq!(game.history().moves.len(), 4);
+        assert_eq!(game.position().turn(), Color::White);
+        // with nobody to reply, one move at a time
+        assert!(game.undo_turn(None) && game.undo_turn(None));
+        assert_eq!(game.history().moves.len(), 2);
+        assert!(game.redo_turn(None));
+        assert_eq!(game.history().moves.len(), 3);
+        assert_eq!(game.position().turn(), Color::Black);
+        // the computer's reply was never played, so it is left to move
+        game.undo_turn(None);
+        game.play_notation("d4").expect("legal move");
+        assert!(!game.redo_turn(computer));
+        assert!(game.undo_turn(computer));
+        assert!(game.redo_turn(computer));
+        assert_eq!(game.history().moves.len(), 3);
+        assert_eq!(game.position().turn(), Color::Black);
+
+        // a computer moving first has only its move taken back, and then moves again
+        let mut game = Game::new();
+        game.play_notation("e4").expect("legal move");
+        assert!(game.undo_turn(Some(Color::White)));
+        assert!(game.history().moves.is_empty());
+        assert_eq!(game.position().turn(), Color::White);
+        assert!(!game.undo_turn(Some(Color::White)));
+    }
+
+    #[test]
+    /// a saved game carries on from the same position, history, clock and options
+    fn saved_game_round_trip() {
+        let mut game = Game::new();
+        game.set_clock(Clock::new(TimeControls::both(
+            "300+2".parse().expect("valid"),
+        )));
+        for notation in ["e4", "e5", "Nf3", "Nc6", "Bb5"] {
+            game.play_notation(notation).expect("legal move");
+        }
+        assert!(game.undo());
+        let options = GameOptions {
+            computer: Some(Color::Black),
+            ..Default::default()
+        };
+        let json = SavedGame::new(&game, &options).to_json().expect("saves");
+        assert!(json.contains("\"version\": 1"));
+        assert!(json.contains("\"300+2\""));
+
+        let mut saved = SavedGame::from_json(&json).expect("valid save");
+        assert_eq!(saved.options.computer, Some(Color::Black));
+        assert_eq!(saved.game.position().fen(), game.position().fen());
+        assert_eq!(saved.game.pgn(), game.pgn());
+        assert_eq!(saved.game.history().hashes, game.history().hashes);
+        // the clock runs again for the player to move, and the undone move can be redone
+        let reading = saved.game.clock().expect("timed").reading(Instant::now());
+        assert_eq!(reading.running, Some(Color::White));
+        assert!(reading.white > 290_000);
+        assert!(saved.game.redo());
+        assert_eq!(saved.game.history().moves.len(), 5);
+        saved.game.play_notation("a6").expect("legal move");
+
+        // files from a newer release, or not saves at all, are refused
+        let newer = json.replace("\"version\": 1", "\"version\": 2");
+        assert!(SavedGame::from_json(&newer).is_err());
+        assert!(SavedGame::from_json("{}").is_err());
+        assert!(SavedGame::from_json("not json").is_err());
+    }
+
+    #[test]
+    /// divide splits the node count up by first move
+    fn perft_divide() {
+        let mut position = Position::from_fen(PERFT_POSITIONS[1].0).expect("valid FEN");
+        let split = divide(&mut position, 2);
+        assert_eq!(split.len(), 48);
+        assert_eq!(split.iter().map(|(_, nodes)| nodes).sum::<u64>(), 2039);
+        let nodes = |text: &str| {
+            split
+                .iter()
+                .find(|(uci, _)| uci == text)
+                .map(|(_, nodes)| *nodes)
+        };
+        assert_eq!(nodes("e1g1"), Some(43));
+        assert_eq!(nodes("d5e6"), Some(46));
+        assert_eq!(nodes("a2a4"), Some(44));
+        assert_eq!(nodes("e1e2"), None);
+        let mut position =
+            Position::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").expect("valid FEN");
+        let promotions: Vec<String> = divide(&mut position, 1)
+            .into_iter()
+            .map(|(uci, _)| uci)
+            .filter(|uci| uci.starts_with("b7"))
+            .collect();
+        assert_eq!(promotions, ["b7b8q", "b7b8r", "b7b8b", "b7b8n"]);
+    }
+
+    #[test]
+    /// making and unmaking every legal move leaves the position exactly as it was,
+    /// and matches applying the move to a copy
+    fn position_make_and_unmake() {
+        for (fen, _) in PERFT_POSITIONS {
+            let mut position = Position::from_fen(fen).expect("valid FEN");
+            let start = position.clone();
+            for mv in start.legal_moves() {
+                let undo = position.make(mv);
+                assert_eq!(position, start.apply(mv), "{} {:?}", fen, mv);
+                position.unmake(&undo);
+                assert_eq!(position, start, "{} {:?}", fen, mv);
+            }
+        }
+        let position =
+            Position::from_fen("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1").expect("valid FEN");
+        let moves = position.legal_moves();
+        let castles = moves
+            .iter()
+            .filter(|mv| mv.kind == MoveType::Castle)
+            .count();
+        let promotions = moves.iter().filter(|mv| mv.promotion.is_some()).count();
+        assert_eq!((castles, promotions), (2, 8)); // b8 and bxa8, each to Q, R, B or N
+        let castled = position.apply(
+            moves[moves
+                .iter()
+                .position(|mv| mv.kind == MoveType::Castle)
+                .unwrap()],
+        );
+        assert_eq!(castled.turn(), Color::Black);
+    }
+
+    #[test]
+    /// a game can be played through the public API alone
+    fn game_api() {
+        assert_eq!(
+            Position::from_fen(START_FEN).expect("valid FEN"),
+            Position::new()
+        );
+        let mut game = Game::new();
+        assert_eq!(game.position().moves_from((6, 0)).len(), 2);
+        assert!(game.position().moves_from((6, 7)).is_empty()); // not black's turn
+        game.play((4, 1), (4, 3), None).expect("e4 is legal");
+        assert!(game.play((4, 3), (4, 4), None).is_err());
+        game.play_notation("e5").expect("e5 is legal");
+        assert!(game.play_notation("Ke3").is_err());
+        game.play_notation("g1f3").expect("Nf3 is legal");
+        assert_eq!(game.position().turn(), Color::Black);
+        assert!(game.pgn().contains("1. e4 e5 2. Nf3 *"));
+        assert!(game.undo());
+        assert_eq!(
+            game.position().fen(),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2"
+        );
+        assert!(game.redo());
+        assert!(!game.redo());
+        let replayed = Game::from_pgn(&game.pgn()).expect("valid PGN");
+        assert_eq!(replayed.position(), game.position());
+
+        let mut game = Game::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").expect("valid FEN");
+        assert!(game.history().start_fen.is_some());
+        assert!(game.position().is_promotion((1, 6), (1, 7)));
+        assert!(!game.position().is_promotion((4, 0), (4, 1)));
+        // the promotion piece is part of the move, so a move without one is refused outright
+        assert!(game.play((1, 6), (1, 7), None).is_err());
+        assert!(game.play((1, 6), (1, 7), Some(PieceKind::King)).is_err());
+        assert!(game.play((4, 0), (4, 1), Some(PieceKind::Queen)).is_err());
+        assert_eq!(game.position().turn(), Color::White);
+        game.play((1, 6), (1, 7), Some(PieceKind::Rook))
+            .expect("b8=R is legal");
+        assert_eq!(game.history().moves[0].san, "b8=R+");
+        assert_eq!(game.position().turn(), Color::Black);
+        assert!(game.pgn().contains("1. b8=R+"));
+        assert!(game.claim_draw().is_err());
+        game.time_out(Color::Black);
+        assert_eq!(
+            game.position().result(),
+            GameResult::win_for(Color::White, Termination::Timeout)
+        );
+        assert!(game.play_notation("Kd7").is_err());
+    }
+
+    #[test]
+    /// squares and pieces are named as the UI names them, and squares off the board are refused
+    fn squares_and_pieces() {
+        let mut game = Game::new();
+        // squares off the board are refused rather than looked up
+        assert!(game.play((8, 0), (4, 3), None).is_err());
+        assert!(game.play((4, 1), (4, usize::MAX), None).is_err());
+        assert!(game.position().moves_from((0, 8)).is_empty());
+        assert!(!game.position().is_promotion((1, 6), (8, 7)));
+        assert!(game.history().moves.is_empty());
+        // square names off the board are refused rather than wrapping round
+        assert_eq!(square_to_coord("h8").expect("on the board"), (7, 7));
+        for name in ["a9", "a0", "i1", "b", "b10", "é1"] {
+            assert!(square_to_coord(name).is_err(), "{name}");
+        }
+        // pieces are named as the UI's chessboard names them, for checking dropped pieces
+        game.play_notation("Nf3").expect("Nf3 is legal");
+        let board = game.position().board();
+        assert_eq!(piece_name(&board.get((5, 2))).as_deref(), Some("wN"));
+        assert_eq!(piece_name(&board.get((4, 6))).as_deref(), Some("bP"));
+        assert_eq!(piece_name(&board.get((4, 7))).as_deref(), Some("bK"));
+        assert_eq!(piece_name(&board.get((4, 2))), None);
+    }
+
+    #[test]
+    /// the terminal client draws the board either way up and plays typed moves and commands
+    fn terminal_client() {
+        let ascii = BoardStyle {
+            unicode: false,
+            ..BoardStyle::default()
+        };
+        let board = Position::new().board().clone();
+        let drawing = pretty_print_board(&board, &ascii);
+        let lines: Vec<&str> = drawing.lines().collect();
+        assert_eq!(lines[0], "8  r n b q k b n r");
+        assert_eq!(lines[7], "1  R N B Q K B N R");
+        assert_eq!(lines[8], "   a b c d e f g h");
+        let flipped = BoardStyle {
+            orientation: Color::Black,
+            ..ascii
+        };
+        let drawing = pretty_print_board(&board, &flipped);
+        assert!(drawing.starts_with("1  R N B K Q B N R"), "{drawing}");
+        assert!(drawing.ends_with("   h g f e d c b a\n"), "{drawing}");
+        assert!(pretty_print_board(&board, &BoardStyle::default()).contains('♞'));
+
+        let args = |args: &[&str]| parse_args(args.iter().map(|arg| arg.to_string()), false);
+        let options = args(&["--computer", "white", "--depth", "1", "--ascii"]).expect("valid");
+        assert_eq!(options.computer, Some(Color::White));
+        assert_eq!(options.engine.depth, 1);
+        assert_eq!(options.style.orientation, Color::Black); // facing the player
+        assert!(!options.style.colour && !options.style.unicode);
+        assert!(args(&["--colour"]).expect("valid").style.colour);
+        assert!(args(&["--computer", "green"]).is_err());
+        assert!(args(&["--depth", "0"]).is_err());
+        assert!(args(&["--fast"]).is_err());
+
+        let ascii_options = || CliOptions {
+            style: ascii,
+            ..CliOptions::default()
+        };
+        let session = |input: &str, options| {
+            let mut output = Vec::new();
+            play(input.as_bytes(), &mut output, &options).expect("game played");
+            String::from_utf8(output).expect("UTF-8")
+        };
+        let output = session(
+            "e4\ne7e5\nNf3\nundo\nfen\nQh5\npgn\nnew\nfen\n",
+            ascii_options(),
+        );
+        assert!(output.contains("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2"));
+        assert!(output.contains("1. e4 e5 2. Qh5 *"), "{output}");
+        assert!(output.contains(START_FEN));
+        // a draw can only be claimed once it is offered, here on the third repetition
+        let output = session(
+            "draw\nNf3\nNf6\nNg1\nNg8\nNf3\nNf6\nNg1\nNg8\ndraw\n",
+            ascii_options(),
+        );
+        assert!(output.contains("there is no draw to claim"), "{output}");
+        assert!(
+            output.contains("White to move, able to claim a draw by ThreefoldRepetition"),
+            "{output}"
+        );
+        assert!(output.contains("1/2-1/2 ThreefoldRepetition"), "{output}");
+        let output = session("Ke2\nquit\ne4\n", ascii_options());
+        assert!(output.contains("Ke2: "), "{output}");
+        assert!(!output.contains("Black to move"), "{output}");
+
+        // the computer replies to each move, and undo takes back both
+        let computer = parse_args(["--computer".to_string(), "black".to_string()], false)
+            .map(|options| CliOptions {
+                engine: EngineSettings {
+                    depth: 1,
+                    move_time: None,
+                    ..options.engine
+                },
+                ..options
+            })
+            .expect("valid");
+        let output = session("d4\nundo\nfen\n", computer);
+        assert_eq!(output.matches("computer plays").count(), 1, "{output}");
+        assert!(output.contains(START_FEN));
+    }
+}
@@ -0,0 +1,202 @@
+//! General utility functions for chess
+
+use super::bitboard::Bitboards;
+use super::board::BoardState;
+use super::notation::piece_letter;
+use super::types::{Color, GameMeta, MoveList, MoveType, Piece, PieceKind, Square};
+use anyhow::{anyhow, Result};
+
+/// Convert letters a-h to a row index from a standard chessboard
+pub fn letter_to_row(letter: char) -> Result<usize> {
+    Ok(match letter {
+        'a' => 0,
+        'b' => 1,
+        'c' => 2,
+        'd' => 3,
+        'e' => 4,
+        'f' => 5,
+        'g' => 6,
+        'h' => 7,
+        _ => return Err(anyhow!("invalid letter")),
+    })
+}
+
+/// check if the piece we are looking is of the opposite colour to us
+pub fn check_enemy(our_color: Color, considered_piece: &Piece) -> bool {
+    (considered_piece.get_colour() == Some(Color::Black) && our_color == Color::White)
+        || (considered_piece.get_colour() == Some(Color::White) && our_color == Color::Black)
+}
+
+/// convert a square string to a coordinate tuple i.e. b3 = (1,2)
+/// col (letter) followed by row (number)
+pub fn square_to_coord(square: &str) -> Result<(usize, usize)> {
+    let sq_vec: Vec<char> = square.chars().collect();
+    if sq_vec.len() != 2 {
+        return Err(anyhow!("square string wasn't 2 characters"));
+    };
+    let Some(digit @ 1..=8) = sq_vec[1].to_digit(10) else {
+        return Err(anyhow!("rank of square '{}' isn't 1 to 8", square));
+    };
+    Ok((letter_to_row(sq_vec[0])?, (digit - 1) as usize))
+}
+
+/// convert a coordinate tuple to a square string i.e. (1,2) = b3
+pub fn coord_to_square(coord: Square) -> String {
+    format!("{}{}", (b'a' + coord.0 as u8) as char, coord.1 + 1)
+}
+
+/// Name a piece as the chessboard in the UI does, colour then letter i.e. wN or bP
+pub fn piece_name(piece: &Piece) -> Option<String> {
+    let color = match piece.get_colour()? {
+        Color::White => 'w',
+        Color::Black => 'b',
+    };
+    Some(format!("{}{}", color, piece_letter(piece).unwrap_or('P')))
+}
+
+/// Return all legal moves for the piece on this square, including castling and en passant
+pub fn piece_moves(source: Square, board: &BoardState, meta: &GameMeta) -> MoveList {
+    if board.get(source).get_colour() != Some(turn_into_colour(meta.turn)) {
+        return MoveList::new();
+    }
+    all_moves(board, meta)
+        .into_iter()
+        .filter(|(from, ..)| *from == source)
+        .map(|(_, target, move_type)| (target, move_type))
+        .collect()
+}
+
+/// Return every legal move available to the player whose turn it is, as (source, target, type)
+pub fn all_moves(board: &BoardState, meta: &GameMeta) -> Vec<(Square, Square, MoveType)> {
+    Bitboards::new(board, meta).legal_moves(turn_into_colour(meta.turn))
+}
+
+/// Check if the square we clicked on is a valid move of the currently selected piece, and what type
+pub fn valid_move(
+    source: Square,
+    target: Square,
+    board: &BoardState,
+    meta: &GameMeta,
+) -> Option<MoveType> {
+    piece_moves(source, board, meta)
+        .into_iter()
+        .find(|(sq, _)| sq == &target)
+        .map(|(_, move_type)| move_type)
+}
+
+/// Check if this square is attacked by any piece of the other colour
+pub fn under_threat(square: Square, our_color: Color, board: &BoardState) -> bool {
+    Bitboards::from(board).attacked(square, our_color.opponent())
+}
+
+/// convert turn number into the corresponding color
+///
+/// assuming White always goes first
+pub fn turn_into_colour(turn: usize) -> Color {
+    if turn.is_multiple_of(2) {
+        Color::White
+    } else {
+        Color::Black
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// How the board is drawn in a terminal
+pub struct BoardStyle {
+    /// The side at the bottom of the board
+    pub orientation: Color,
+    /// Chess symbols such as ♞, rather than letters, uppercase for White
+    pub unicode: bool,
+    /// Shade the squares with ANSI colour codes
+    pub colour: bool,
+}
+
+impl Default for BoardStyle {
+    fn default() -> Self {
+        BoardStyle {
+            orientation: Color::White,
+            unicode: true,
+            colour: false,
+        }
+    }
+}
+
+/// ANSI codes for the light and dark squares, the white and black pieces, and back to normal
+const LIGHT_SQUARE: &str = "\x1b[48;5;180m";
+const DARK_SQUARE: &str = "\x1b[48;5;137m";
+const WHITE_PIECE: &str = "\x1b[1;97m";
+const BLACK_PIECE: &str = "\x1b[1;30m";
+const RESET: &str = "\x1b[0m";
+
+/// Symbol for a piece, or a blank square
+fn piece_symbol(piece: &Piece, style: &BoardStyle) -> char {
+    let (Some(kind), Some(color)) = (piece.kind(), piece.get_colour()) else {
+        return if style.colour { ' ' } else { '.' };
+    };
+    if !style.unicode {
+        return match color {
+            Color::White => kind.letter(),
+            Color::Black => kind.letter().to_ascii_lowercase(),
+        };
+    }
+    // coloured pieces all use the solid symbols, which are easier to make out
+    let solid = style.colour || color == Color::Black;
+    match (kind, solid) {
+        (PieceKind::King, false) => '♔',
+        (PieceKind::Queen, false) => '♕',
+        (PieceKind::Rook, false) => '♖',
+        (PieceKind::Bishop, false) => '♗',
+        (PieceKind::Knight, false) => '♘',
+        (PieceKind::Pawn, false) => '♙',
+        (PieceKind::King, true) => '♚',
+        (PieceKind::Queen, true) => '♛',
+        (PieceKind::Rook, true) => '♜',
+        (PieceKind::Bishop, true) => '♝',
+        (PieceKind::Knight, true) => '♞',
+        (PieceKind::Pawn, true) => '♟',
+    }
+}
+
+/// Draw the board for a terminal in a neat form, with ranks and files labelled
+pub fn pretty_print_board(board: &BoardState, style: &BoardStyle) -> String {
+    let mut ranks: Vec<usize> = (0..8).rev().collect();
+    let mut files: Vec<usize> = (0..8).collect();
+    if style.orientation == Color::Black {
+        ranks.reverse();
+        files.reverse();
+    }
+    let mut text = String::new();
+    for &row in &ranks {
+        text.push_str(&format!("{} ", row + 1));
+        for &col in &files {
+            let piece = board.get((col, row));
+            let symbol = piece_symbol(&piece, style);
+            if style.colour {
+                let square = if (col + row) % 2 == 0 {
+                    DARK_SQUARE
+                } else {
+                    LIGHT_SQUARE
+                };
+                let ink = match piece.get_colour() {
+                    Some(Color::Black) => BLACK_PIECE,
+                    _ => WHITE_PIECE,
+                };
+                text.push_str(&format!("{}{} {} {}", square, ink, symbol, RESET));
+            } else {
+                text.push_str(&format!(" {}", symbol));
+            }
+        }
+        text.push('\n');
+    }
+    text.push_str("  ");
+    for &col in &files {
+        let file = (b'a' + col as u8) as char;
+        if style.colour {
+            text.push_str(&format!(" {} ", file));
+        } else {
+            text.push_str(&format!(" {}", file));
+        }
+    }
+    text.push('\n');
+    text
+}
@@ -0,0 +1,80 @@
+//! Zobrist hashing of positions, used to spot repeated positions
+//!
+//! <https://www.chessprogramming.org/Zobrist_Hashing>
+
+use super::board::BoardState;
+use super::fen::has_castling_right;
+use super::types::{Color, GameMeta, Piece};
+use super::utils::turn_into_colour;
+
+/// 12 piece types on 64 squares, side to move, 4 castling rights and 8 en passant files
+const KEY_COUNT: usize = 12 * 64 + 1 + 4 + 8;
+const SIDE_KEY: usize = 12 * 64;
+const CASTLING_KEYS: usize = SIDE_KEY + 1;
+const EN_PASSANT_KEYS: usize = CASTLING_KEYS + 4;
+
+/// Fixed pseudo-random keys, generated at compile time with splitmix64 so hashes are stable
+const KEYS: [u64; KEY_COUNT] = {
+    let mut keys = [0; KEY_COUNT];
+    let mut state: u64 = 0x7461_7572_6963_6873; // "taurichs"
+    let mut i = 0;
+    while i < KEY_COUNT {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        keys[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    keys
+};
+
+/// Index of a piece into the key table, ignoring its status flags
+fn piece_index(piece: &Piece) -> Option<usize> {
+    let kind = match piece {
+        Piece::None => return None,
+        Piece::Pawn(..) => 0,
+        Piece::Knight(..) => 1,
+        Piece::Bishop(..) => 2,
+        Piece::Rook(..) => 3,
+        Piece::Queen(..) => 4,
+        Piece::King(..) => 5,
+    };
+    match piece.get_colour() {
+        Some(Color::Black) => Some(kind + 6),
+        _ => Some(kind),
+    }
+}
+
+/// Hash the position: pieces, side to move, castling rights and en passant square
+///
+/// The en passant file only counts if a pawn is actually in place to make the capture,
+/// so positions that only differ by an unusable en passant target are the same.
+pub fn position_hash(board: &BoardState, meta: &GameMeta) -> u64 {
+    let mut hash = 0;
+    for col in 0..8 {
+        for row in 0..8 {
+            if let Some(index) = piece_index(&board.get((col, row))) {
+                hash ^= KEYS[index * 64 + col * 8 + row];
+            }
+        }
+    }
+    let turn = turn_into_colour(meta.turn);
+    if turn == Color::Black {
+        hash ^= KEYS[SIDE_KEY];
+    }
+    for (i, right) in ['K', 'Q', 'k', 'q'].into_iter().enumerate() {
+        if has_castling_right(board, right) {
+            hash ^= KEYS[CASTLING_KEYS + i];
+        }
+    }
+    if let Some((col, row)) = meta.en_passant {
+        let capturable = [col.wrapping_sub(1), col + 1]
+            .into_iter()
+            .any(|side| side < 8 && board.get((side, row)) == Piece::Pawn(turn, false));
+        if capturable {
+            hash ^= KEYS[EN_PASSANT_KEYS + col];
+        }
+    }
+    hash
+}
@@ -1,248 +1,866 @@
 //! Logic for the chess board actions
 
-use super::{
+use super::data::{
+    GameData, GameEvent, OptionsData, QueueHandler, SelectedSquare, UciEngineData, UciStopperData,
+};
+use super::error::{Access, ChessError, Result};
+use super::save::{autosave, read_game, write_game, SAVE_FILE};
+use chess_core::{
     board::BoardState,
-    data::{GameMetaData, HistoryData, Message, PieceLocation, QueueHandler, SelectedSquare},
-    moves::check_castling_moves,
-    types::{Color, GameMeta, MoveList, MoveType, Piece, Square},
-    utils::{check_enemy, remove_invalid_moves, square_to_coord, turn_into_colour, valid_move},
+    chess960,
+    clock::{Clock, ClockReading},
+    engine::best_move,
+    notation::{check_suffix, parse_uci},
+    types::{Color, GameMeta, GameOptions, MoveList, PieceKind, Square, StartPosition},
+    uci::{line_to_san, UciEngine, UciInfo, UciLimit},
+    utils::{check_enemy, coord_to_square, piece_name, square_to_coord, turn_into_colour},
+    Game,
 };
-use anyhow::{anyhow, Context};
-use tauri::Result;
+use std::thread;
+use std::time::Instant;
+use tauri::{AppHandle, Manager};
 
 #[tauri::command]
+#[specta::specta]
 /// Get the location of all pieces from global memory
-pub fn get_state(state: tauri::State<PieceLocation>) -> BoardState {
-    let board = state.lock().expect("board state access");
-    board.clone()
+pub fn get_state(state: tauri::State<GameData>) -> Result<BoardState> {
+    let game = state.access("game")?;
+    Ok(game.position().board().clone())
 }
 
 #[tauri::command]
+#[specta::specta]
 /// Get the game score from global memory
-pub fn get_score(state: tauri::State<GameMetaData>) -> GameMeta {
-    let meta_game = state.lock().expect("game state access");
-    *meta_game
+pub fn get_score(state: tauri::State<GameData>) -> Result<GameMeta> {
+    let game = state.access("game")?;
+    Ok(*game.position().meta())
 }
 
 #[tauri::command]
+#[specta::specta]
 /// Initialize a new game by sending a starting set of coords
 pub fn new_game(
-    state: tauri::State<PieceLocation>,
-    meta: tauri::State<GameMetaData>,
-    history_data: tauri::State<HistoryData>,
-) -> BoardState {
+    options: Option<GameOptions>,
+    app: AppHandle,
+    state: tauri::State<GameData>,
+    game_options: tauri::State<OptionsData>,
+) -> Result<BoardState> {
+    let options = options.unwrap_or_default();
+    let new_game = match options.start {
+        StartPosition::Standard => Game::new(),
+        StartPosition::Chess960(index) => Game::chess960(index).map_err(invalid_position)?,
+        StartPosition::RandomChess960 => {
+            Game::chess960(chess960::random_index()).map_err(invalid_position)?
+        }
+    };
     // Lock the counter(Mutex) to get the current value
-    let mut board = state.lock().expect("board state access");
-    let mut game_meta = meta.lock().expect("game state access");
-    let mut history = history_data.lock().expect("game history access");
-    *history = Default::default();
-    // reset game meta data
-    game_meta.new_game();
-    // reset board to empty
-    *board = BoardState::new();
+    let mut game = state.access("game")?;
+    *game = new_game;
+    game.set_auto_claim_draws(options.auto_claim_draws);
+    if let Some(controls) = &options.time_control {
+        game.set_clock(Clock::new(controls.clone()));
+    }
+    engine_reply(&app, game.position().meta(), &options); // the computer may have the first move
+    *game_options.access("game options")? = options;
+    autosave(&app, &game);
+
+    Ok(game.position().board().clone()) // return board state to frontend
+}
 
-    board.clone() // return dereferenced board state to frontend
+#[tauri::command]
+#[specta::specta]
+/// Set up the board from a FEN string, replacing the current game
+pub fn load_fen(
+    fen: &str,
+    app: AppHandle,
+    state: tauri::State<GameData>,
+    clicked: tauri::State<SelectedSquare>,
+    game_options: tauri::State<OptionsData>,
+) -> Result<(BoardState, GameMeta)> {
+    let new_game = Game::from_fen(fen).map_err(invalid_position)?;
+    let mut game = state.access("game")?;
+    replace_game(
+        &mut game,
+        new_game,
+        &mut *game_options.access("game options")?,
+    );
+    *clicked.access("selected square")? = None;
+    autosave(&app, &game);
+    Ok((game.position().board().clone(), *game.position().meta()))
+}
+
+#[tauri::command]
+#[specta::specta]
+/// Get the current position as a FEN string
+pub fn get_fen(state: tauri::State<GameData>) -> Result<String> {
+    let game = state.access("game")?;
+    Ok(game.position().fen())
 }
 
 #[tauri::command]
+#[specta::specta]
+/// Replay a game from PGN, replacing the current game
+pub fn load_pgn(
+    pgn: &str,
+    app: AppHandle,
+    state: tauri::State<GameData>,
+    clicked: tauri::State<SelectedSquare>,
+    game_options: tauri::State<OptionsData>,
+) -> Result<(BoardState, GameMeta)> {
+    let new_game = Game::from_pgn(pgn).map_err(invalid_position)?;
+    let mut game = state.access("game")?;
+    replace_game(
+        &mut game,
+        new_game,
+        &mut *game_options.access("game options")?,
+    );
+    *clicked.access("selected square")? = None;
+    autosave(&app, &game);
+    Ok((game.position().board().clone(), *game.position().meta()))
+}
+
+#[tauri::command]
+#[specta::specta]
+/// Get the game so far as PGN
+pub fn get_pgn(state: tauri::State<GameData>) -> Result<String> {
+    let game = state.access("game")?;
+    Ok(game.pgn())
+}
+
+#[tauri::command]
+#[specta::specta]
 /// Highlight available moves for the piece occupying this square
 pub fn hover_square(
     square: &str,
-    state: tauri::State<PieceLocation>,
+    state: tauri::State<GameData>,
     clicked: tauri::State<SelectedSquare>,
-    meta: tauri::State<GameMetaData>,
 ) -> Result<MoveList> {
-    let board = state.lock().expect("board state access");
-    let game_meta = meta.lock().expect("game state access");
-    let selected = *clicked.lock().expect("selected square access");
-    let mut coord: Square = square_to_coord(square)?;
+    let game = state.access("game")?;
+    let selected = *clicked.access("selected square")?;
+    let mut coord: Square = parse_square(square)?;
     println!("hovering over square {:?}", coord);
-    let turn = turn_into_colour(game_meta.turn);
     if let Some(square) = selected {
         coord = square;
     }
-    let piece = board.get(coord);
-    let mut move_options = piece.get_moves(coord, &board);
-    if piece.is_king(turn) {
-        for castle_move in check_castling_moves(coord, turn, &board) {
-            move_options.push(castle_move);
-        }
-    } else if piece == Piece::Pawn(turn, false) {
-        if let Some(en_passant) = game_meta.en_passant {
-            // does this piece have a valid en passant target?
-            for pawn_move in piece.get_en_passant_moves(coord, en_passant) {
-                move_options.push(pawn_move);
-            }
-        }
-    }
-    move_options = remove_invalid_moves(move_options, coord, &game_meta, &board);
-    Ok(move_options)
+    Ok(game.position().moves_from(coord))
 }
 
 #[tauri::command]
+#[specta::specta]
 /// Remove any highlighting for square just left
 pub fn unhover_square(_square: &str) -> bool {
     true
 }
 
 #[tauri::command]
-/// Perform the boardstate change associated with a chess piece being moved
-pub fn drop_square(source_square: &str, target_square: &str, piece: &str) {
-    dbg!(source_square, target_square, piece);
+#[specta::specta]
+/// Move a piece dragged from one square and dropped on another, if that is legal
+///
+/// `piece` is the piece the UI thinks is being moved, i.e. wN, which must be the one on the board.
+pub fn drop_square(
+    source_square: &str,
+    target_square: &str,
+    piece: &str,
+    app: AppHandle,
+    state: tauri::State<GameData>,
+    clicked: tauri::State<SelectedSquare>,
+) -> Result<(MoveList, BoardState, GameMeta)> {
+    let mut game = state.access("game")?;
+    *clicked.access("selected square")? = None;
+    ensure_playing(&app, &mut game)?;
+    ensure_player_turn(&game, &*app.state::<OptionsData>().access("game options")?)?;
+    let auto_queen = auto_queen(&app)?;
+    match drop_piece(&mut game, source_square, target_square, piece, auto_queen)? {
+        Dropped::Played => moved(&app, &game)?,
+        Dropped::NeedsPromotion(source, target) => ask_promotion(&app, source, target)?,
+    }
+    Ok((
+        MoveList::new(),
+        game.position().board().clone(),
+        *game.position().meta(),
+    ))
 }
 
 #[tauri::command]
+#[specta::specta]
 /// Click on a square to select or deselect it.
 ///
 /// If a square is selected, the hover command will be deactivated.
 /// If a square is a valid move of the selected piece, move that piece.
 pub fn click_square(
     square: &str,
-    state: tauri::State<PieceLocation>,
+    app: AppHandle,
+    state: tauri::State<GameData>,
     clicked: tauri::State<SelectedSquare>,
-    meta: tauri::State<GameMetaData>,
-    history_data: tauri::State<HistoryData>,
-    queue: tauri::State<QueueHandler>,
 ) -> Result<(MoveList, BoardState, GameMeta)> {
     // acquire control of global data
-    let mut board = state.lock().expect("board state access");
-    let mut game_meta = meta.lock().expect("game state access");
-    let mut selected = *clicked.lock().expect("selected square access");
+    let mut game = state.access("game")?;
+    let mut selected = *clicked.access("selected square")?;
 
     let mut move_list = MoveList::new();
-    if game_meta.game_over {
-        // game over, do nothing
-        return Ok((move_list, board.clone(), *game_meta));
-    }
-    let coord = square_to_coord(square)?;
-    let turn = turn_into_colour(game_meta.turn);
-    let piece = board.get(coord);
+    ensure_playing(&app, &mut game)?;
+    ensure_player_turn(&game, &*app.state::<OptionsData>().access("game options")?)?;
+    let coord = parse_square(square)?;
+    let turn = game.position().turn();
+    let piece = game.position().board().get(coord);
     let contains_enemy = check_enemy(turn, &piece);
-    if selected.is_none() {
-        // 1.if we have nothing selected and the new coordinate doesn't contain an enemy piece, select it!
-        if !contains_enemy {
-            move_list = piece.get_moves(coord, &board);
-            if piece.is_king(turn) {
-                for castle_move in check_castling_moves(coord, turn, &board) {
-                    move_list.push(castle_move);
-                }
-            } else if piece == Piece::Pawn(turn, false) {
-                if let Some(en_passant) = game_meta.en_passant {
-                    // does this piece have a valid en passant target?
-                    for pawn_move in piece.get_en_passant_moves(coord, en_passant) {
-                        move_list.push(pawn_move);
-                    }
-                }
-            };
-            move_list = remove_invalid_moves(move_list, coord, &game_meta, &board);
+    if let Some(source) = selected {
+        let promotes = game.position().is_promotion(source, coord);
+        if source == coord {
+            // 2. if we have clicked on the same square again, unselect it
+            selected = None;
+        } else if promotes && !auto_queen(&app)? {
+            // 3. a pawn reaching the end waits for the player to pick its promotion
+            ask_promotion(&app, source, coord)?;
+            selected = None;
+        } else if game
+            .play(source, coord, promotes.then_some(PieceKind::Queen))
+            .is_ok()
+        {
+            // 4. if we have clicked a valid move of selected, the move has been made
+            println!("valid move");
+            selected = None;
+            moved(&app, &game)?;
+            println!("score history: {:?}", game.history().score);
+        } else {
+            // 6. select the new square as this isn't a valid move
+            move_list = game.position().moves_from(coord);
             selected = if move_list.is_empty() {
                 None
             } else {
                 Some(coord)
-            }
+            };
         }
-    } else if selected == Some(coord) {
-        // 2. if we have clicked on the same square again, unselect it
-        selected = None;
-    } else {
-        println!("possible valid move, check");
-        match valid_move(selected.unwrap(), coord, &board, &game_meta, turn) {
-            Some(move_type) => {
-                // 4. if we have clicked a valid move of selected, do move
-                println!("valid move");
-                let source = selected.unwrap();
-                let mover = board.get(source).has_moved();
-                board.set(coord, Piece::None); // empty the destination square
-                board.set(source, Piece::None); // take moving out of its square
-                board.set(coord, mover); // place moving in the new square
-                game_meta.en_passant = None; // clear any previous en passant target
-                match move_type {
-                    MoveType::Castle => {
-                        println!("need to move rook too");
-                        let start_col = if coord.0 > 4 { 7 } else { 0 };
-                        let dest_col = if coord.0 > 4 { 5 } else { 3 };
-                        let row = coord.1;
-                        let mover = board.get((start_col, row)).has_moved();
-                        board.set((start_col, row), Piece::None); // take castling rook out of its square
-                        board.set((dest_col, row), mover); // place castling rook in the new square
-                    }
-                    MoveType::EnPassant => {
-                        println!("need to remove pawn too");
-                        board.set((coord.0, source.1), Piece::None);
-                    }
-                    MoveType::Double => {
-                        println!("register an en passant target");
-                        game_meta.en_passant = Some(coord);
-                    }
-                    _ => {
-                        // normal move or capture
-                        if mover.is_promotable_pawn(coord) {
-                            game_meta.promotable_pawn = Some(coord);
-                            queue
-                                .lock()
-                                .expect("queue access")
-                                .blocking_send(Message::new("promotion", &coord)?)
-                                .context("failed to send promotion event")?;
-                        }
-                    }
-                }
-                if mover.is_king(turn) {
-                    match turn {
-                        Color::Black => {
-                            game_meta.black_king.piece = mover;
-                            game_meta.black_king.square = coord;
-                        }
-                        Color::White => {
-                            game_meta.white_king.piece = mover;
-                            game_meta.white_king.square = coord;
-                        }
-                    }
-                }
-                selected = None;
-                // 5. update the meta only if something has changed
-                let mut history = history_data.lock().expect("game history access");
-                game_meta.new_turn(&mut board, &mut history);
-                println!("score history: {:?}", history.score);
-            }
-            None => {
-                // 6. select the new square as this isn't a valid move
-                selected = Some(coord);
-                move_list = board.get(coord).get_moves(coord, &board);
-                move_list = remove_invalid_moves(move_list, coord, &game_meta, &board);
-                if move_list.is_empty() {
-                    selected = None;
-                }
-            }
+    } else if !contains_enemy {
+        // 1.if we have nothing selected and the new coordinate doesn't contain an enemy piece, select it!
+        move_list = game.position().moves_from(coord);
+        selected = if move_list.is_empty() {
+            None
+        } else {
+            Some(coord)
+        }
+    }
+    *clicked.access("selected square")? = selected;
+    Ok((
+        move_list,
+        game.position().board().clone(),
+        *game.position().meta(),
+    ))
+}
+
+#[tauri::command]
+#[specta::specta]
+/// Make a move typed in SAN or long algebraic notation, i.e. Nf3, exd5, e7e8q or O-O
+pub fn enter_move(
+    notation: &str,
+    app: AppHandle,
+    state: tauri::State<GameData>,
+    clicked: tauri::State<SelectedSquare>,
+    game_options: tauri::State<OptionsData>,
+) -> Result<(MoveList, BoardState, GameMeta)> {
+    let mut game = state.access("game")?;
+    ensure_playing(&app, &mut game)?;
+    ensure_player_turn(&game, &*game_options.access("game options")?)?;
+    game.play_notation(notation)
+        .map_err(|error| ChessError::IllegalMove(format!("{:#}", error)))?;
+    announce_move(&app, &game)?;
+    let game_meta = game.position().meta();
+    *clicked.access("selected square")? = None;
+    engine_reply(&app, game_meta, &*game_options.access("game options")?);
+    autosave(&app, &game);
+    Ok((MoveList::new(), game.position().board().clone(), *game_meta))
+}
+
+#[tauri::command]
+#[specta::specta]
+/// Take back the last move
+///
+/// Against the computer its reply is taken back as well, so it is the player's turn again.
+pub fn undo_move(
+    app: AppHandle,
+    state: tauri::State<GameData>,
+    clicked: tauri::State<SelectedSquare>,
+) -> Result<(BoardState, GameMeta)> {
+    let options = app.state::<OptionsData>().access("game options")?.clone();
+    let mut game = state.access("game")?;
+    if !game.undo_turn(options.computer) {
+        return Err(ChessError::NothingToUndo);
+    }
+    // the computer moved first, so plays again
+    engine_reply(&app, game.position().meta(), &options);
+    autosave(&app, &game);
+    *clicked.access("selected square")? = None;
+    Ok((game.position().board().clone(), *game.position().meta()))
+}
+
+#[tauri::command]
+#[specta::specta]
+/// Play the last move taken back again
+///
+/// Against the computer its reply is played again too, or searched for if there wasn't one.
+pub fn redo_move(
+    app: AppHandle,
+    state: tauri::State<GameData>,
+    clicked: tauri::State<SelectedSquare>,
+) -> Result<(BoardState, GameMeta)> {
+    let options = app.state::<OptionsData>().access("game options")?.clone();
+    let mut game = state.access("game")?;
+    if !game.redo_turn(options.computer) {
+        return Err(ChessError::NothingToRedo);
+    }
+    announce_move(&app, &game)?;
+    engine_reply(&app, game.position().meta(), &options);
+    autosave(&app, &game);
+    *clicked.access("selected square")? = None;
+    Ok((game.position().board().clone(), *game.position().meta()))
+}
+
+#[tauri::command]
+#[specta::specta]
+/// Get the moves played so far in SAN
+pub fn get_move_list(state: tauri::State<GameData>) -> Result<Vec<String>> {
+    let game = state.access("game")?;
+    Ok(game
+        .history()
+        .moves
+        .iter()
+        .map(|record| record.san.clone())
+        .collect())
+}
+
+#[tauri::command]
+#[specta::specta]
+/// Hash of the current position as hex, and how many times it has occurred
+pub fn get_position_hash(state: tauri::State<GameData>) -> Result<(String, usize)> {
+    let game = state.access("game")?;
+    Ok((
+        format!("{:016x}", game.position().hash()),
+        game.position().meta().repetitions,
+    ))
+}
+
+#[tauri::command]
+#[specta::specta]
+/// End the game in a draw, if the player to move is able to claim one
+pub fn claim_draw(app: AppHandle, state: tauri::State<GameData>) -> Result<GameMeta> {
+    let mut game = state.access("game")?;
+    game.claim_draw().map_err(|_| ChessError::NoDrawToClaim)?;
+    announce_result(&app, game.position().meta())?;
+    Ok(*game.position().meta())
+}
+
+#[tauri::command]
+#[specta::specta]
+/// A player's clock has run out, losing them the game unless their opponent can't mate
+///
+/// Timed games keep their own clock, so only a flag that has really fallen counts.
+pub fn time_out(color: Color, app: AppHandle, state: tauri::State<GameData>) -> Result<GameMeta> {
+    let mut game = state.access("game")?;
+    if game.clock().is_none() {
+        if game.position().result().is_over() {
+            return Err(ChessError::GameOver);
         }
+        game.time_out(color);
+    } else if game.check_flag() != Some(color) {
+        return Err(ChessError::TimeRemaining(color));
+    }
+    announce_result(&app, game.position().meta())?;
+    Ok(*game.position().meta())
+}
+
+#[tauri::command]
+#[specta::specta]
+/// Read the clock of a timed game
+pub fn get_clock(state: tauri::State<GameData>) -> Result<Option<ClockReading>> {
+    let game = state.access("game")?;
+    Ok(game.clock().map(|clock| clock.reading(Instant::now())))
+}
+
+/// Send the clock reading of a running game to the frontend, ending the game if a flag has fallen
+pub fn tick_clock(app: &AppHandle) -> Result<()> {
+    let state = app.state::<GameData>();
+    let mut game = state.access("game")?;
+    let flagged = game.check_flag();
+    let Some(clock) = game.clock() else {
+        return Ok(());
+    };
+    if clock.running().is_none() && flagged.is_none() {
+        return Ok(()); // nothing has changed since the last tick
     }
-    *clicked.lock().expect("clicked square access") = selected;
-    Ok((move_list, board.to_owned(), game_meta.to_owned()))
+    send_event(app, GameEvent::ClockTick(clock.reading(Instant::now())))?;
+    if flagged.is_some() {
+        announce_result(app, game.position().meta())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+/// Let the computer choose and play a move for the player to move
+///
+/// The search runs on its own thread, and the move is sent back through the event queue.
+pub fn play_engine_move(app: AppHandle, state: tauri::State<GameData>) -> Result<()> {
+    let game = state.access("game")?;
+    if game.position().result().is_over() {
+        return Err(ChessError::GameOver);
+    }
+    spawn_engine(app);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+/// Start an external UCI engine, which then plays and analyses instead of the built-in one
+///
+/// Returns the name of the engine.
+pub fn start_uci_engine(
+    path: &str,
+    uci: tauri::State<UciEngineData>,
+    stopper: tauri::State<UciStopperData>,
+) -> Result<String> {
+    let mut engine = UciEngine::spawn(path).map_err(engine_error)?;
+    engine.new_game().map_err(engine_error)?;
+    let name = engine.name.clone();
+    let new_stopper = engine.stopper();
+    stop_search(&stopper)?;
+    *uci.access("UCI engine")? = Some(engine);
+    *stopper.access("UCI engine stopper")? = Some(new_stopper);
+    Ok(name)
 }
 
 #[tauri::command]
-/// User has selected a piece type for the promotion of a valid pawn
-pub fn promote(
-    choice: char,
-    state: tauri::State<PieceLocation>,
-    meta: tauri::State<GameMetaData>,
-    queue: tauri::State<QueueHandler>,
+#[specta::specta]
+/// Shut down the external UCI engine, going back to the built-in one
+pub fn stop_uci_engine(
+    uci: tauri::State<UciEngineData>,
+    stopper: tauri::State<UciStopperData>,
 ) -> Result<()> {
-    let mut game_meta = meta.lock().expect("game state access");
-    let colour = turn_into_colour(game_meta.turn + 1);
-    if let Some(coord) = game_meta.promotable_pawn {
-        let mut board = state.lock().expect("board state access");
-        let rx = queue.lock().expect("queue access");
-        game_meta.promotable_pawn = None;
-        let promotion = match choice {
-            'Q' => Piece::Queen(colour, false),
-            'K' => Piece::Knight(colour, false),
-            'R' => Piece::Rook(colour, false),
-            'B' => Piece::Bishop(colour, false),
-            _ => return Err(anyhow!("invalid promotion choice"))?,
-        };
-        board.set(coord, promotion);
-        rx.blocking_send(Message::new("board", &*board)?)
-            .context("failed to send board state")?;
+    // a search holds the engine until it ends, so end it first
+    stop_search(&stopper)?;
+    uci.access("UCI engine")?.take();
+    stopper.access("UCI engine stopper")?.take();
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+/// Analyse the current position with the external UCI engine
+///
+/// Progress is streamed as `uci_info` events with the line in SAN, then the best move
+/// is sent as a `uci_bestmove` event.
+pub fn analyse_position(limit: UciLimit, app: AppHandle) -> Result<()> {
+    if app.state::<UciEngineData>().access("UCI engine")?.is_none() {
+        return Err(ChessError::Engine("no UCI engine is running".to_string()));
+    }
+    thread::spawn(move || {
+        if let Err(error) = analyse(&app, limit) {
+            eprintln!("error while analysing: {:?}", error);
+        }
+    });
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+/// Save the game to the app data directory, to be loaded again later
+pub fn save_game(
+    app: AppHandle,
+    state: tauri::State<GameData>,
+    game_options: tauri::State<OptionsData>,
+) -> Result<()> {
+    let game = state.access("game")?;
+    let options = game_options.access("game options")?.clone();
+    write_game(&app, SAVE_FILE, &game, &options)
+}
+
+#[tauri::command]
+#[specta::specta]
+/// Load the game last saved with `save_game`, along with the options it was played with
+pub fn load_game(
+    app: AppHandle,
+    state: tauri::State<GameData>,
+    clicked: tauri::State<SelectedSquare>,
+    game_options: tauri::State<OptionsData>,
+) -> Result<BoardState> {
+    let saved = read_game(&app, SAVE_FILE)?;
+    let mut game = state.access("game")?;
+    *game = saved.game;
+    *clicked.access("selected square")? = None;
+    engine_reply(&app, game.position().meta(), &saved.options);
+    *game_options.access("game options")? = saved.options;
+    autosave(&app, &game);
+    Ok(game.position().board().clone())
+}
+
+/// Swap in a newly loaded game, keeping the player's preferences
+///
+/// The loaded game has no clock and nobody playing the computer, so the options say so too.
+fn replace_game(game: &mut Game, mut new_game: Game, options: &mut GameOptions) {
+    new_game.set_auto_claim_draws(game.position().meta().auto_claim_draws);
+    *game = new_game;
+    options.computer = None;
+    options.time_control = None;
+}
+
+/// Read a square name from the UI, i.e. e4
+fn parse_square(square: &str) -> Result<Square> {
+    square_to_coord(square).map_err(|_| ChessError::InvalidSquare(square.to_string()))
+}
+
+/// A FEN, PGN or starting position that couldn't be set up
+fn invalid_position(error: anyhow::Error) -> ChessError {
+    ChessError::InvalidPosition(format!("{:#}", error))
+}
+
+/// A failure of the external UCI engine
+fn engine_error(error: anyhow::Error) -> ChessError {
+    ChessError::Engine(format!("{:#}", error))
+}
+
+/// Stop any search by the external engine, so it can be taken away
+///
+/// An engine that can't be told has already quit, and is dropped all the same.
+fn stop_search(stopper: &UciStopperData) -> Result<()> {
+    if let Some(stopper) = stopper.access("UCI engine stopper")?.as_ref() {
+        let _ = stopper.stop();
+    }
+    Ok(())
+}
+
+/// Check the game is still being played, first ending it if the player to move has run out of time
+fn ensure_playing(app: &AppHandle, game: &mut Game) -> Result<()> {
+    if game.check_flag().is_some() {
+        announce_result(app, game.position().meta())?;
+    }
+    if game.position().result().is_over() {
+        return Err(ChessError::GameOver);
+    }
+    Ok(())
+}
+
+/// Refuse a move made in the UI while the computer is to move, which would take its turn away
+fn ensure_player_turn(game: &Game, options: &GameOptions) -> Result<()> {
+    if options.computer == Some(game.position().turn()) {
+        return Err(ChessError::NotYourTurn);
+    }
+    Ok(())
+}
+
+/// What became of a piece dropped on the board
+#[derive(Debug, PartialEq)]
+enum Dropped {
+    /// The move was played
+    Played,
+    /// The pawn reached the end, so the player is asked which piece it becomes
+    NeedsPromotion(Square, Square),
+}
+
+/// Play a piece dragged in the UI from `source_square` to `target_square`, checking `piece` is
+/// the one on the board
+///
+/// A pawn reaching the end becomes a queen with `auto_queen`, otherwise the board is left as it is
+/// until the player picks a piece.
+fn drop_piece(
+    game: &mut Game,
+    source_square: &str,
+    target_square: &str,
+    piece: &str,
+    auto_queen: bool,
+) -> Result<Dropped> {
+    let source = parse_square(source_square)?;
+    let target = parse_square(target_square)?;
+    let on_board = piece_name(&game.position().board().get(source));
+    if on_board.as_deref() != Some(piece) {
+        return Err(ChessError::WrongPiece(format!(
+            "{} was dropped from {}, which holds {}",
+            piece,
+            source_square,
+            on_board.as_deref().unwrap_or("nothing")
+        )));
+    }
+    let promotes = game.position().is_promotion(source, target);
+    if promotes && !auto_queen {
+        return Ok(Dropped::NeedsPromotion(source, target));
+    }
+    play_move(game, source, target, promotes.then_some(PieceKind::Queen))?;
+    Ok(Dropped::Played)
+}
+
+/// Play a move made in the UI, saying exactly why if it can't be
+fn play_move(
+    game: &mut Game,
+    source: Square,
+    target: Square,
+    promotion: Option<PieceKind>,
+) -> Result<()> {
+    let piece = game.position().board().get(source);
+    if check_enemy(game.position().turn(), &piece) {
+        return Err(ChessError::NotYourTurn);
+    }
+    let promotes = game.position().is_promotion(source, target);
+    match promotion {
+        None if promotes => {
+            return Err(ChessError::InvalidPromotion(
+                "choose a piece for the pawn".to_string(),
+            ));
+        }
+        Some(kind) if !promotes => {
+            return Err(ChessError::InvalidPromotion(format!(
+                "only a pawn reaching the end can become a {:?}",
+                kind
+            )));
+        }
+        Some(kind @ (PieceKind::Pawn | PieceKind::King)) => {
+            return Err(ChessError::InvalidPromotion(format!(
+                "a pawn can't become a {:?}",
+                kind
+            )));
+        }
+        _ => {}
+    }
+    game.play(source, target, promotion)
+        .map_err(|error| ChessError::IllegalMove(format!("{:#}", error)))
+}
+
+/// Whether the player has chosen to always promote pawns to a queen
+fn auto_queen(app: &AppHandle) -> Result<bool> {
+    Ok(app
+        .state::<OptionsData>()
+        .access("game options")?
+        .auto_queen)
+}
+
+/// Ask the player which piece a pawn is promoted to, leaving the board as it is
+///
+/// The event carries the move in UCI form, i.e. e7e8, for the UI to play with `enter_move`
+/// once the piece has been picked, i.e. as e7e8n.
+fn ask_promotion(app: &AppHandle, source: Square, target: Square) -> Result<()> {
+    let mv = coord_to_square(source) + &coord_to_square(target);
+    send_event(app, GameEvent::PromotionRequired(mv))
+}
+
+/// Follow up a move made in the UI: announce it, let the computer reply and autosave
+fn moved(app: &AppHandle, game: &Game) -> Result<()> {
+    announce_move(app, game)?;
+    let game_meta = game.position().meta();
+    let options = app.state::<OptionsData>().access("game options")?.clone();
+    engine_reply(app, game_meta, &options);
+    autosave(app, game);
+    Ok(())
+}
+
+/// Queue an event to be emitted to the frontend
+pub fn send_event(app: &AppHandle, event: GameEvent) -> Result<()> {
+    let queue = app.state::<QueueHandler>();
+    let tx = queue.access("event queue")?;
+    // the queue is closed once the app starts shutting down
+    let tx = tx.as_ref().ok_or(ChessError::ShuttingDown)?;
+    tx.blocking_send(event)
+        .map_err(|_| ChessError::ShuttingDown)
+}
+
+/// Let the frontend know a move has been played, and whether it gives check or ends the game
+fn announce_move(app: &AppHandle, game: &Game) -> Result<()> {
+    if let Some(record) = game.history().moves.last() {
+        send_event(app, GameEvent::MoveMade(record.san.clone()))?;
+    }
+    let game_meta = game.position().meta();
+    if check_suffix(game_meta) == "+" {
+        send_event(app, GameEvent::Check(turn_into_colour(game_meta.turn)))?;
+    }
+    announce_result(app, game_meta)
+}
+
+/// Let the frontend know if the game has just come to an end
+fn announce_result(app: &AppHandle, game_meta: &GameMeta) -> Result<()> {
+    if game_meta.result.is_over() {
+        send_event(app, GameEvent::GameOver(game_meta.result))?;
+    }
+    Ok(())
+}
+
+/// Start the computer's move if it plays the side now to move
+pub fn engine_reply(app: &AppHandle, game_meta: &GameMeta, options: &GameOptions) {
+    if options.computer == Some(turn_into_colour(game_meta.turn)) && !game_meta.result.is_over() {
+        spawn_engine(app.clone());
+    }
+}
+
+/// Search for a move away from the UI thread
+fn spawn_engine(app: AppHandle) {
+    thread::spawn(move || {
+        if let Err(error) = engine_move(&app) {
+            eprintln!("error while playing engine move: {:?}", error);
+        }
+    });
+}
+
+/// Search the current position and play the best move found, unless the game has
+/// moved on while searching
+fn engine_move(app: &AppHandle) -> Result<()> {
+    let state = app.state::<GameData>();
+    // search on a copy, so the game isn't locked while thinking
+    let start = state.access("game")?.clone();
+    let position = start.position();
+    let settings = app.state::<OptionsData>().access("game options")?.engine;
+    let uci_data = app.state::<UciEngineData>();
+    let mut uci = uci_data.access("UCI engine")?;
+    let found = match uci.as_mut() {
+        // an external engine takes over from the built-in one while it is running
+        Some(engine) => {
+            engine
+                .set_position(start.history(), position.is_chess960())
+                .map_err(engine_error)?;
+            let limit = match settings.move_time {
+                Some(millis) => UciLimit::MoveTime(millis),
+                None => UciLimit::Depth(settings.depth.into()),
+            };
+            let best = engine.go(limit, |_| Ok(())).map_err(engine_error)?;
+            Some(parse_uci(&best, position.board(), position.meta()).map_err(engine_error)?)
+        }
+        None => best_move(position, &settings),
     };
+    drop(uci);
+    let Some(mv) = found else {
+        return Ok(());
+    };
+
+    let mut game = state.access("game")?;
+    if game.position() != position {
+        return Ok(()); // a move was made or a new game started in the meantime
+    }
+    game.play(mv.from, mv.to, mv.promotion)
+        .map_err(engine_error)?;
+    *app.state::<SelectedSquare>().access("selected square")? = None;
+    send_event(
+        app,
+        GameEvent::BoardChanged(Box::new(game.position().board().clone())),
+    )?;
+    announce_move(app, &game)?;
+    autosave(app, &game);
     Ok(())
 }
+
+/// Run the external engine on the current position, streaming its progress to the frontend
+fn analyse(app: &AppHandle, limit: UciLimit) -> Result<()> {
+    let game = app.state::<GameData>().access("game")?.clone();
+    let (board, game_meta) = (game.position().board(), game.position().meta());
+    let uci_data = app.state::<UciEngineData>();
+    let mut uci = uci_data.access("UCI engine")?;
+    let Some(engine) = uci.as_mut() else {
+        return Ok(()); // stopped in the meantime
+    };
+    engine
+        .set_position(game.history(), game.position().is_chess960())
+        .map_err(engine_error)?;
+    let best = engine
+        .go(limit, |info| {
+            let info = UciInfo {
+                pv: line_to_san(&info.pv, board, game_meta),
+                ..info
+            };
+            Ok(send_event(app, GameEvent::EngineInfo(info))?)
+        })
+        .map_err(engine_error)?;
+    let best_san = line_to_san(&[best], board, game_meta);
+    send_event(app, GameEvent::EngineBestMove(best_san.first().cloned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The SAN of the last move played
+    fn last_san(game: &Game) -> &str {
+        &game.history().moves.last().expect("a move was played").san
+    }
+
+    #[test]
+    /// a dropped piece must be the one on the board, of the player to move
+    fn drop_checks_piece() {
+        let mut game = Game::new();
+        assert!(matches!(
+            drop_piece(&mut game, "e2", "e4", "wN", false),
+            Err(ChessError::WrongPiece(_))
+        ));
+        assert_eq!(
+            drop_piece(&mut game, "e3", "e4", "wP", false),
+            Err(ChessError::WrongPiece(
+                "wP was dropped from e3, which holds nothing".to_string()
+            ))
+        );
+        assert_eq!(
+            drop_piece(&mut game, "e9", "e4", "wP", false),
+            Err(ChessError::InvalidSquare("e9".to_string()))
+        );
+        assert_eq!(
+            drop_piece(&mut game, "e7", "e5", "bP", false),
+            Err(ChessError::NotYourTurn)
+        );
+        assert!(matches!(
+            drop_piece(&mut game, "e2", "e5", "wP", false),
+            Err(ChessError::IllegalMove(_))
+        ));
+        assert!(game.history().moves.is_empty());
+        assert_eq!(
+            drop_piece(&mut game, "g1", "f3", "wN", false),
+            Ok(Dropped::Played)
+        );
+        assert_eq!(last_san(&game), "Nf3");
+    }
+
+    #[test]
+    /// the player can't move for the computer while it is thinking
+    fn computer_turn_refused() {
+        let mut game = Game::new();
+        let options = GameOptions {
+            computer: Some(Color::Black),
+            ..Default::default()
+        };
+        assert_eq!(ensure_player_turn(&game, &options), Ok(()));
+        game.play_notation("e4").expect("legal move");
+        assert_eq!(
+            ensure_player_turn(&game, &options),
+            Err(ChessError::NotYourTurn)
+        );
+        assert_eq!(ensure_player_turn(&game, &GameOptions::default()), Ok(()));
+    }
+
+    #[test]
+    /// en passant, castling and promotion can all be played by dragging
+    fn drop_special_moves() {
+        let mut game = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2").expect("valid FEN");
+        assert_eq!(
+            drop_piece(&mut game, "e5", "d6", "wP", false),
+            Ok(Dropped::Played)
+        );
+        assert_eq!(last_san(&game), "exd6");
+        assert!(game.position().board().get((3, 4)).get_colour().is_none());
+
+        let mut game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").expect("valid FEN");
+        assert_eq!(
+            drop_piece(&mut game, "e1", "g1", "wK", false),
+            Ok(Dropped::Played)
+        );
+        assert_eq!(last_san(&game), "O-O");
+        assert_eq!(
+            drop_piece(&mut game, "e8", "c8", "bK", false),
+            Ok(Dropped::Played)
+        );
+        assert_eq!(last_san(&game), "O-O-O");
+
+        // the player is asked for a piece, and the board is left alone until then
+        let fen = "4k3/1P6/8/8/8/8/8/4K3 w - - 0 1";
+        let mut game = Game::from_fen(fen).expect("valid FEN");
+        assert_eq!(
+            drop_piece(&mut game, "b7", "b8", "wP", false),
+            Ok(Dropped::NeedsPromotion((1, 6), (1, 7)))
+        );
+        assert_eq!(game.position().fen(), fen);
+        assert_eq!(
+            drop_piece(&mut game, "b7", "b8", "wP", true),
+            Ok(Dropped::Played)
+        );
+        assert_eq!(last_san(&game), "b8=Q+");
+    }
+}
@@ -0,0 +1,92 @@
+//! TypeScript bindings for the commands, and every type crossing into the frontend
+//!
+//! A test fails if the checked in file is stale, and writes it again when run with
+//! `UPDATE_BINDINGS=1`, i.e. `UPDATE_BINDINGS=1 cargo test bindings`.
+
+use super::{api, data::GameEvent};
+use tauri::Wry;
+use tauri_specta::{collect_commands, Builder};
+
+/// Collect the commands and event types, also used to handle the commands
+pub fn builder() -> Builder<Wry> {
+    Builder::<Wry>::new()
+        .commands(collect_commands![
+            api::new_game,
+            api::get_state,
+            api::get_score,
+            api::load_fen,
+            api::get_fen,
+            api::load_pgn,
+            api::get_pgn,
+            api::hover_square,
+            api::unhover_square,
+            api::drop_square,
+            api::click_square,
+            api::enter_move,
+            api::get_move_list,
+            api::undo_move,
+            api::redo_move,
+            api::get_position_hash,
+            api::claim_draw,
+            api::time_out,
+            api::get_clock,
+            api::save_game,
+            api::load_game,
+            api::play_engine_move,
+            api::start_uci_engine,
+            api::stop_uci_engine,
+            api::analyse_position,
+            crate::event_tester,
+        ])
+        // events are emitted by name rather than as tauri-specta events, see `GameEvent::name`
+        .typ::<GameEvent>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use specta_typescript::{BigIntExportBehavior, Typescript};
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// Where the bindings are written, relative to the `src-tauri` directory
+    const BINDINGS_FILE: &str = "../src/types/bindings.ts";
+
+    /// Path of the bindings in the source tree, wherever the app or tests are run from
+    fn bindings_path() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(BINDINGS_FILE)
+    }
+
+    /// Helpers for typed events and channels, generated whether or not there are any
+    ///
+    /// Events are emitted by name instead, see `GameEvent::name`, so these are read once here to
+    /// pass the compiler's `noUnusedLocals` check with the rest of the file still checked.
+    const UNUSED_HELPERS: &str = "void TAURI_CHANNEL;\nvoid __makeEvents__;";
+
+    /// TypeScript as the frontend expects it
+    ///
+    /// Clock times and counts are well within the range of a JavaScript number, so are not bigints.
+    fn typescript() -> Typescript {
+        Typescript::default()
+            .bigint(BigIntExportBehavior::Number)
+            .header(UNUSED_HELPERS)
+    }
+
+    #[test]
+    /// the checked in bindings match the commands and types, or are written again if asked
+    fn bindings_are_up_to_date() {
+        let bindings = builder()
+            .export_str(typescript())
+            .expect("failed to generate bindings");
+        let path = bindings_path();
+        if std::env::var_os("UPDATE_BINDINGS").is_some() {
+            fs::write(&path, &bindings).expect("failed to write bindings");
+        }
+        let checked_in = fs::read_to_string(&path).expect("failed to read bindings");
+        assert!(
+            bindings == checked_in,
+            "{} is stale, run `UPDATE_BINDINGS=1 cargo test bindings` to write it again",
+            path.display()
+        );
+    }
+}
@@ -1,51 +1,196 @@
-use super::types;
-use crate::chess::board::BoardState;
-use anyhow::Result;
-use serde::{Deserialize, Serialize};
+use chess_core::{
+    board::BoardState,
+    clock::ClockReading,
+    types::{self, Color, GameResult},
+    uci::{self, UciInfo},
+    Game,
+};
+use serde::Serialize;
 use std::sync::Mutex;
+use std::thread::JoinHandle;
 use tauri::{async_runtime::Receiver, Emitter as _, WebviewWindow}; // mutual exclusion wrapper
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
-pub struct Message {
-    pub event: &'static str,
-    pub payload: String,
+#[derive(Clone, Debug, Serialize, PartialEq, specta::Type)]
+/// Something that happened in the backend which the frontend didn't ask for, i.e. the computer
+/// replying or a clock running down
+pub enum GameEvent {
+    /// The pieces have moved other than by a command from the UI
+    BoardChanged(Box<BoardState>),
+    /// A move was played, in SAN
+    MoveMade(String),
+    /// The player to move is in check
+    Check(Color),
+    /// The game has just come to an end
+    GameOver(GameResult),
+    /// A pawn is to be promoted, carrying the move in UCI form, i.e. e7e8
+    PromotionRequired(String),
+    /// The clock of a running game
+    ClockTick(ClockReading),
+    /// Progress of the external engine's analysis, with the line in SAN
+    EngineInfo(UciInfo),
+    /// The move the external engine settled on in its analysis, in SAN
+    EngineBestMove(Option<String>),
+    /// Sent by the test page
+    Test(String),
 }
-impl Message {
-    pub fn new<T: Serialize>(event: &'static str, payload: &T) -> Result<Self> {
-        Ok(Self {
-            event,
-            payload: serde_json::to_string(payload)?,
-        })
+
+impl GameEvent {
+    /// Name of the event the frontend listens for
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameEvent::BoardChanged(_) => "board",
+            GameEvent::MoveMade(_) => "move",
+            GameEvent::Check(_) => "check",
+            GameEvent::GameOver(_) => "game_over",
+            GameEvent::PromotionRequired(_) => "promotion",
+            GameEvent::ClockTick(_) => "clock",
+            GameEvent::EngineInfo(_) => "uci_info",
+            GameEvent::EngineBestMove(_) => "uci_bestmove",
+            GameEvent::Test(_) => "test",
+        }
+    }
+    /// Payload of the event as the listener expects it, without the name wrapped around it
+    pub fn payload(&self) -> serde_json::Result<serde_json::Value> {
+        match self {
+            GameEvent::BoardChanged(board) => serde_json::to_value(board),
+            GameEvent::MoveMade(san) => serde_json::to_value(san),
+            GameEvent::Check(color) => serde_json::to_value(color),
+            GameEvent::GameOver(result) => serde_json::to_value(result),
+            GameEvent::PromotionRequired(mv) => serde_json::to_value(mv),
+            GameEvent::ClockTick(reading) => serde_json::to_value(reading),
+            GameEvent::EngineInfo(info) => serde_json::to_value(info),
+            GameEvent::EngineBestMove(san) => serde_json::to_value(san),
+            GameEvent::Test(text) => serde_json::to_value(text),
+        }
+    }
+    /// Emit the event to the frontend
+    pub fn emit(&self, window: &WebviewWindow) -> tauri::Result<()> {
+        window.emit(self.name(), self.payload()?)
     }
 }
 
-/// Handler event data from Rust -> Frontend
-pub fn queue_handler(window: &WebviewWindow, rx: &mut Receiver<Message>) -> Result<()> {
-    if let Some(res) = rx.blocking_recv() {
-        match res.event {
-            "board" => {
-                let board: BoardState = serde_json::from_str(&res.payload)?;
-                window.emit("board", board)?;
-            }
-            other => {
-                println!("{}, {}", other, res.payload);
-                window.emit(other, res.payload)?;
-            }
+/// Handle events from Rust -> Frontend until the queue is closed, passing each one to `emit`
+///
+/// An event that can't be emitted is reported and skipped, so one failure doesn't stop the rest.
+/// Returns how many events were lost that way.
+pub fn queue_handler(
+    rx: &mut Receiver<GameEvent>,
+    mut emit: impl FnMut(&GameEvent) -> tauri::Result<()>,
+) -> usize {
+    let mut failures = 0;
+    while let Some(event) = rx.blocking_recv() {
+        // clock ticks and engine progress are too frequent to log
+        if !matches!(event, GameEvent::ClockTick(_) | GameEvent::EngineInfo(_)) {
+            println!("{}: {:?}", event.name(), event);
+        }
+        if let Err(error) = emit(&event) {
+            eprintln!("error while emitting {} event: {}", event.name(), error);
+            failures += 1;
         }
     }
-    Ok(())
+    failures
 }
 
-/// game move history
-pub type HistoryData = Mutex<types::Hist>;
-
-/// Game state 8x8 board, filled with empty space or Pieces
-pub type PieceLocation = Mutex<BoardState>;
+/// The game being played: the pieces, metadata and move history
+pub type GameData = Mutex<Game>;
 
 /// Track which square has been selected in the UI
 pub type SelectedSquare = Mutex<Option<types::Square>>;
 
-/// Game score stored relative to white
-pub type GameMetaData = Mutex<types::GameMeta>;
-/// queue handler
-pub type QueueHandler = Mutex<tauri::async_runtime::Sender<Message>>;
+/// Settings the current game was started with
+pub type OptionsData = Mutex<types::GameOptions>;
+
+/// External engine, while one is running
+pub type UciEngineData = Mutex<Option<uci::UciEngine>>;
+/// Stops the external engine's search, without waiting for the engine while it is searching
+pub type UciStopperData = Mutex<Option<uci::UciStopper>>;
+/// Sending end of the event queue, taken away when the app shuts down to close the queue
+pub type QueueHandler = Mutex<Option<tauri::async_runtime::Sender<GameEvent>>>;
+/// Thread emitting the events, joined when the app shuts down
+pub type QueueThread = Mutex<Option<JoinHandle<usize>>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess_core::{types::Termination, uci::UciScore};
+    use serde_json::json;
+    use std::thread;
+    use tauri::async_runtime::channel;
+
+    #[test]
+    /// each event keeps the name the frontend listens for, and sends its payload unwrapped
+    fn event_names_and_payloads() {
+        let board = BoardState::default();
+        let events = [
+            (
+                GameEvent::BoardChanged(Box::new(board.clone())),
+                "board",
+                serde_json::to_value(&board).unwrap(),
+            ),
+            (GameEvent::MoveMade("Nf3".to_string()), "move", json!("Nf3")),
+            (GameEvent::Check(Color::Black), "check", json!("Black")),
+            (
+                GameEvent::GameOver(GameResult::WhiteWins(Termination::Checkmate)),
+                "game_over",
+                json!({ "WhiteWins": "Checkmate" }),
+            ),
+            (
+                GameEvent::PromotionRequired("e7e8".to_string()),
+                "promotion",
+                json!("e7e8"),
+            ),
+            (
+                GameEvent::ClockTick(ClockReading {
+                    white: 1000,
+                    black: 2000,
+                    running: Some(Color::White),
+                }),
+                "clock",
+                json!({ "white": 1000, "black": 2000, "running": "White" }),
+            ),
+            (
+                GameEvent::EngineInfo(UciInfo {
+                    depth: Some(3),
+                    score: Some(UciScore::Mate(2)),
+                    pv: vec!["Qh5".to_string()],
+                }),
+                "uci_info",
+                json!({ "depth": 3, "score": { "Mate": 2 }, "pv": ["Qh5"] }),
+            ),
+            (GameEvent::EngineBestMove(None), "uci_bestmove", json!(null)),
+            (GameEvent::Test("hello".to_string()), "test", json!("hello")),
+        ];
+        for (event, name, payload) in events {
+            assert_eq!(event.name(), name);
+            assert_eq!(event.payload().unwrap(), payload, "{} payload", name);
+        }
+    }
+
+    #[test]
+    /// the queue carries on past an event it couldn't emit, and ends once the sender is dropped
+    fn queue_shutdown() {
+        let (sender, mut receiver) = channel(8);
+        let handler = thread::spawn(move || {
+            let mut emitted = Vec::new();
+            let failures = queue_handler(&mut receiver, |event| {
+                if let GameEvent::Check(_) = event {
+                    return Err(tauri::Error::AssetNotFound("check".to_string()));
+                }
+                emitted.push(event.name());
+                Ok(())
+            });
+            (failures, emitted)
+        });
+        for event in [
+            GameEvent::MoveMade("e4".to_string()),
+            GameEvent::Check(Color::White),
+            GameEvent::Test("hello".to_string()),
+        ] {
+            sender.blocking_send(event).unwrap();
+        }
+        drop(sender);
+        let (failures, emitted) = handler.join().unwrap();
+        assert_eq!(failures, 1);
+        assert_eq!(emitted, ["move", "test"]);
+    }
+}
@@ -0,0 +1,149 @@
+//! Errors returned by the chess commands, sent to the frontend as `{ kind, message }`
+
+use chess_core::types::Color;
+use serde::Serialize;
+use std::fmt::Display;
+use std::sync::{Mutex, MutexGuard};
+
+/// Result of a chess command
+pub type Result<T> = std::result::Result<T, ChessError>;
+
+#[derive(Clone, Debug, Serialize, PartialEq, specta::Type)]
+#[serde(tag = "kind", content = "message")]
+/// Everything that can go wrong in a command, so the frontend can tell the player exactly what
+pub enum ChessError {
+    /// A square name that isn't on the board, i.e. a9
+    InvalidSquare(String),
+    /// A move the rules don't allow, or notation that can't be read as a move
+    IllegalMove(String),
+    /// A piece belonging to the player who isn't to move
+    NotYourTurn,
+    /// A dragged piece that isn't the one on the square it came from
+    WrongPiece(String),
+    /// A promotion piece given for a move that isn't a promotion, or one a pawn can't become
+    InvalidPromotion(String),
+    /// The game has already ended
+    GameOver,
+    /// There is no move to take back
+    NothingToUndo,
+    /// There is no move taken back to play again
+    NothingToRedo,
+    /// The player to move has no draw they are able to claim
+    NoDrawToClaim,
+    /// A player whose flag hasn't fallen was said to have run out of time
+    TimeRemaining(Color),
+    /// A FEN, PGN or Chess960 starting position that couldn't be set up
+    InvalidPosition(String),
+    /// The external UCI engine failed, or isn't running
+    Engine(String),
+    /// A game couldn't be saved or loaded
+    Save(String),
+    /// Shared state left unusable by a panic on another thread
+    StateUnavailable(&'static str),
+    /// The app is closing, so events can no longer be sent to the frontend
+    ShuttingDown,
+    /// Events couldn't be sent to the frontend, or anything else unexpected
+    Internal(String),
+}
+
+impl Display for ChessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChessError::InvalidSquare(square) => write!(f, "'{}' is not a square", square),
+            ChessError::IllegalMove(reason) => write!(f, "illegal move: {}", reason),
+            ChessError::NotYourTurn => write!(f, "it is not your turn"),
+            ChessError::WrongPiece(reason) => write!(f, "wrong piece: {}", reason),
+            ChessError::InvalidPromotion(reason) => write!(f, "invalid promotion: {}", reason),
+            ChessError::GameOver => write!(f, "the game is over"),
+            ChessError::NothingToUndo => write!(f, "there is no move to undo"),
+            ChessError::NothingToRedo => write!(f, "there is no move to redo"),
+            ChessError::NoDrawToClaim => write!(f, "there is no draw to claim"),
+            ChessError::TimeRemaining(color) => write!(f, "{:?} still has time left", color),
+            ChessError::InvalidPosition(reason) => write!(f, "invalid position: {}", reason),
+            ChessError::Engine(reason) => write!(f, "engine error: {}", reason),
+            ChessError::Save(reason) => write!(f, "save error: {}", reason),
+            ChessError::StateUnavailable(state) => write!(f, "the {} is unavailable", state),
+            ChessError::ShuttingDown => write!(f, "the app is shutting down"),
+            ChessError::Internal(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ChessError {}
+
+impl From<anyhow::Error> for ChessError {
+    fn from(error: anyhow::Error) -> Self {
+        ChessError::Internal(format!("{:#}", error))
+    }
+}
+
+impl From<tauri::Error> for ChessError {
+    fn from(error: tauri::Error) -> Self {
+        ChessError::Internal(error.to_string())
+    }
+}
+
+/// Lock shared state, reporting a mutex poisoned by a panic instead of panicking as well
+pub trait Access<T> {
+    fn access(&self, name: &'static str) -> Result<MutexGuard<'_, T>>;
+}
+
+impl<T> Access<T> for Mutex<T> {
+    fn access(&self, name: &'static str) -> Result<MutexGuard<'_, T>> {
+        self.lock().map_err(|_| ChessError::StateUnavailable(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    /// errors reach the frontend as a kind, with a message only for those that carry one
+    fn serialized_shape() {
+        let shape = |error: ChessError| serde_json::to_value(error).expect("serializes");
+        assert_eq!(
+            shape(ChessError::InvalidSquare("a9".to_string())),
+            json!({ "kind": "InvalidSquare", "message": "a9" })
+        );
+        assert_eq!(
+            shape(ChessError::NotYourTurn),
+            json!({ "kind": "NotYourTurn" })
+        );
+        assert_eq!(
+            shape(ChessError::TimeRemaining(Color::Black)),
+            json!({ "kind": "TimeRemaining", "message": "Black" })
+        );
+        assert_eq!(
+            shape(ChessError::StateUnavailable("game")),
+            json!({ "kind": "StateUnavailable", "message": "game" })
+        );
+        assert_eq!(
+            shape(ChessError::ShuttingDown),
+            json!({ "kind": "ShuttingDown" })
+        );
+    }
+
+    #[test]
+    /// a mutex poisoned by a panic is reported by name, instead of panicking again
+    fn poisoned_state() {
+        let state = Arc::new(Mutex::new(0));
+        let poisoner = Arc::clone(&state);
+        let _ = thread::spawn(move || {
+            let _guard = poisoner.lock().unwrap();
+            panic!("poison the state");
+        })
+        .join();
+        assert_eq!(
+            state.access("game").err(),
+            Some(ChessError::StateUnavailable("game"))
+        );
+        assert_eq!(
+            ChessError::StateUnavailable("game").to_string(),
+            "the game is unavailable"
+        );
+    }
+}
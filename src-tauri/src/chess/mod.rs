@@ -1,10 +1,7 @@
-//! Chess module
+//! Chess module, connecting the rules in `chess_core` to the Tauri frontend
 
 pub mod api;
-mod board;
+pub mod bindings;
 pub mod data;
-mod moves;
-mod pieces;
-mod types;
-mod unit_tests;
-mod utils;
+pub mod error;
+pub mod save;
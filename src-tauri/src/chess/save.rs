@@ -0,0 +1,71 @@
+//! Saving games to the app data directory, so they survive the window being closed
+
+use super::api::engine_reply;
+use super::data::{GameData, OptionsData};
+use super::error::{Access, ChessError, Result};
+use chess_core::{save::SavedGame, types::GameOptions, Game};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+/// Game saved by the player
+pub const SAVE_FILE: &str = "saved_game.json";
+/// Game saved after every move, and carried on with at startup
+pub const AUTOSAVE_FILE: &str = "autosave.json";
+
+/// Path of a save file, creating the app data directory if need be
+fn save_path(app: &AppHandle, file: &str) -> Result<PathBuf> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|error| ChessError::Save(format!("no app data directory: {}", error)))?;
+    fs::create_dir_all(&dir).map_err(|error| {
+        ChessError::Save(format!("failed to create {}: {}", dir.display(), error))
+    })?;
+    Ok(dir.join(file))
+}
+
+/// Write a game and the options it is played with to a save file
+pub fn write_game(app: &AppHandle, file: &str, game: &Game, options: &GameOptions) -> Result<()> {
+    let json = SavedGame::new(game, options)
+        .to_json()
+        .map_err(|error| ChessError::Save(format!("{:#}", error)))?;
+    let path = save_path(app, file)?;
+    fs::write(&path, json)
+        .map_err(|error| ChessError::Save(format!("failed to write {}: {}", path.display(), error)))
+}
+
+/// Read a game back from a save file
+pub fn read_game(app: &AppHandle, file: &str) -> Result<SavedGame> {
+    let path = save_path(app, file)?;
+    let json = fs::read_to_string(&path).map_err(|error| {
+        ChessError::Save(format!("failed to read {}: {}", path.display(), error))
+    })?;
+    SavedGame::from_json(&json).map_err(|error| ChessError::Save(format!("{:#}", error)))
+}
+
+/// Save the game after a move, reporting rather than failing the move if it can't be written
+pub fn autosave(app: &AppHandle, game: &Game) {
+    let saved = app
+        .state::<OptionsData>()
+        .access("game options")
+        .and_then(|options| write_game(app, AUTOSAVE_FILE, game, &options));
+    if let Err(error) = saved {
+        eprintln!("error while autosaving: {}", error);
+    }
+}
+
+/// Carry on with the game autosaved last time the app was run, if there is one
+pub fn resume(app: &AppHandle) -> Result<()> {
+    if !save_path(app, AUTOSAVE_FILE)?.exists() {
+        return Ok(());
+    }
+    let saved = read_game(app, AUTOSAVE_FILE)?;
+    let game_data = app.state::<GameData>();
+    let mut game = game_data.access("game")?;
+    *game = saved.game;
+    // the computer may have been left to move when the app was closed
+    engine_reply(app, game.position().meta(), &saved.options);
+    *app.state::<OptionsData>().access("game options")? = saved.options;
+    Ok(())
+}
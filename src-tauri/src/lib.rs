@@ -2,55 +2,83 @@
 
 mod chess;
 
-use anyhow::Context;
-use chess::data::{queue_handler, Message};
+use chess::data::{queue_handler, GameEvent, QueueHandler, QueueThread};
+use chess::error::{Access, ChessError};
 use std::thread;
-use tauri::{async_runtime::channel, Manager, Result};
+use std::time::Duration;
+use tauri::{async_runtime::channel, AppHandle, Manager, RunEvent};
+
+/// How often the clock of a running game is sent to the UI
+const CLOCK_TICK: Duration = Duration::from_millis(200);
 
 #[tauri::command]
-fn event_tester(queue: tauri::State<chess::data::QueueHandler>) -> Result<()> {
-    let rx = queue.lock().expect("failed to lock queue");
-    rx.blocking_send(Message::new("test", &"hello from Rust")?)
-        .context("failed to send event")?;
-    Ok(())
+#[specta::specta]
+fn event_tester(app: AppHandle) -> chess::error::Result<()> {
+    chess::api::send_event(&app, GameEvent::Test("hello from Rust".to_string()))
+}
+
+/// Close the event queue and wait for the events already sent to reach the frontend
+fn shut_down_queue(app: &AppHandle) {
+    let closed = app
+        .state::<QueueHandler>()
+        .access("event queue")
+        .map(|mut queue| queue.take());
+    let thread = app
+        .state::<QueueThread>()
+        .access("event thread")
+        .map(|mut thread| thread.take());
+    let (Ok(_), Ok(Some(thread))) = (closed, thread) else {
+        eprintln!("the event queue couldn't be shut down cleanly");
+        return;
+    };
+    match thread.join() {
+        Ok(0) => {}
+        Ok(failures) => eprintln!("{} events couldn't be sent to the UI", failures),
+        Err(_) => eprintln!("the event queue thread panicked"),
+    }
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let (sender, mut receiver) = channel::<Message>(5);
+    let bindings = chess::bindings::builder();
+    let (sender, mut receiver) = channel::<GameEvent>(5);
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .setup(|app| {
+            if let Err(error) = chess::save::resume(app.handle()) {
+                eprintln!("error while resuming the last game: {}", error);
+            }
             let window = app
                 .get_webview_window("main")
                 .ok_or_else(|| anyhow::anyhow!("Failed to get the window"))?;
-            thread::spawn(move || {
-                println!("spawning a new thread to handle unprompted events from Rust to the UI");
-                loop {
-                    if let Err(error) = queue_handler(&window, &mut receiver) {
-                        eprintln!("error while handling queue: {:?}", error);
-                        break;
-                    }
+            println!("spawning a new thread to handle unprompted events from Rust to the UI");
+            let queue_thread =
+                thread::spawn(move || queue_handler(&mut receiver, |event| event.emit(&window)));
+            *app.state::<QueueThread>().access("event thread")? = Some(queue_thread);
+            let handle = app.handle().clone();
+            thread::spawn(move || loop {
+                thread::sleep(CLOCK_TICK);
+                match chess::api::tick_clock(&handle) {
+                    Ok(()) => {}
+                    Err(ChessError::ShuttingDown) => break,
+                    Err(error) => eprintln!("error while ticking clock: {}", error),
                 }
             });
             Ok(())
         })
-        .manage(chess::data::PieceLocation::default())
-        .manage(chess::data::GameMetaData::default())
+        .manage(chess::data::GameData::default())
         .manage(chess::data::SelectedSquare::default())
-        .manage(chess::data::HistoryData::default())
-        .manage(chess::data::QueueHandler::new(sender))
-        .invoke_handler(tauri::generate_handler![
-            chess::api::new_game,
-            chess::api::get_state,
-            chess::api::get_score,
-            chess::api::hover_square,
-            chess::api::unhover_square,
-            chess::api::drop_square,
-            chess::api::click_square,
-            chess::api::promote,
-            event_tester,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .manage(chess::data::OptionsData::default())
+        .manage(chess::data::UciEngineData::default())
+        .manage(chess::data::UciStopperData::default())
+        .manage(QueueHandler::new(Some(sender)))
+        .manage(QueueThread::default())
+        .invoke_handler(bindings.invoke_handler())
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let RunEvent::Exit = event {
+                shut_down_queue(app);
+            }
+        });
 }
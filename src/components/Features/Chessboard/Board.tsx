@@ -26,7 +26,8 @@ const Board: React.FC<ChessboardProps> = ({
   boardStyle,
   onPieceClick,
   onDragOverSquare,
-  // onDrop,
+  draggable,
+  onDrop,
   // onSquareRightClick,
   // width,
   onMouseOutSquare,
@@ -53,6 +54,7 @@ const Board: React.FC<ChessboardProps> = ({
           lightSquareStyle={lightSquareStyle}
           darkSquareStyle={darkSquareStyle}
           onDragOverSquare={onDragOverSquare}
+          onDrop={onDrop}
           onSquareClick={onSquareClick}
           onMouseOverSquare={onMouseOverSquare}
           onMouseOutSquare={onMouseOutSquare}
@@ -73,6 +75,7 @@ const Board: React.FC<ChessboardProps> = ({
               width={560}
               square={square}
               onPieceClick={onPieceClick}
+              draggable={draggable}
               id={`${col}${row}${position[square]}`}
               piece={position[square]}
             />
@@ -1,7 +1,9 @@
 import { cn } from 'utils';
 import type { CSSProperties, ReactNode } from 'react';
+import { useDrop } from 'react-dnd';
 import { coordToSquare } from '../chess';
-import type { Orientation, Square } from 'types';
+import type { DraggedPiece } from './Piece';
+import type { Orientation, Piece, Square } from 'types';
 
 export interface BoardSquareProps {
   col: number;
@@ -14,7 +16,11 @@ export interface BoardSquareProps {
   onMouseOverSquare?: (square: Square) => void;
   onMouseOutSquare?: (square: Square) => void;
   onDragOverSquare?: (square: Square) => void;
-  onDrop?: (square: Square) => void;
+  onDrop?: (obj: {
+    sourceSquare: Square;
+    targetSquare: Square;
+    piece: Piece;
+  }) => void;
   onSquareClick?: (square: Square) => void;
   onSquareRightClick?: (square: Square) => void;
 }
@@ -34,9 +40,22 @@ export const BoardSquare: React.FC<BoardSquareProps> = ({
   onMouseOverSquare = () => null,
   onMouseOutSquare = () => null,
   onSquareClick = () => null,
+  onDrop = () => null,
   children,
 }) => {
   const square = coordToSquare(col, row);
+  const [, drop] = useDrop(
+    () => ({
+      accept: 'piece',
+      drop: (item: DraggedPiece) =>
+        onDrop({
+          sourceSquare: item.square,
+          targetSquare: square,
+          piece: item.piece,
+        }),
+    }),
+    [square, onDrop],
+  );
   /** Handles right and left clicks on a board square */
   const handleClick = (event: React.MouseEvent) => {
     if (event.type === 'click') {
@@ -53,6 +72,7 @@ export const BoardSquare: React.FC<BoardSquareProps> = ({
   const rotate = orientation === 'black' ? 'rotate-180' : '';
   return (
     <div
+      ref={drop}
       className={cn('tooltip', rotate)}
       data-tip={`(col ${col}, row ${row}) = ${square}`}
       onMouseEnter={() => onMouseOverSquare(square)}
@@ -1,3 +1,4 @@
+import { useDrag } from 'react-dnd';
 import type { Orientation, Piece, Square } from 'types';
 import pieceSVG from './svg/chesspieces/standard';
 import { cn } from 'utils';
@@ -10,19 +11,37 @@ type PieceProps = {
   connectDragSource?: () => void;
   onPieceClick?: (piece: Piece) => void;
   isDragging?: boolean;
+  draggable?: boolean;
   orientation?: Orientation;
   className?: string;
 };
 
+/** What is carried while a piece is dragged, see BoardSquare for where it is dropped */
+export type DraggedPiece = { piece: Piece; square: Square };
+
 export default function ChessPiece({
   piece,
+  square,
   // isDragging = false,
+  draggable = false,
   width,
   onPieceClick,
   className = '',
 }: PieceProps) {
+  const [{ isDragging }, drag] = useDrag(
+    () => ({
+      type: 'piece',
+      item: { piece, square },
+      canDrag: draggable && piece !== undefined,
+      collect: (monitor) => ({ isDragging: monitor.isDragging() }),
+    }),
+    [piece, square, draggable],
+  );
   return (
-    <div className={cn('h-full', className)}>
+    <div
+      ref={drag}
+      className={cn('h-full', isDragging && 'opacity-50', className)}
+    >
       {piece && (
         <svg
           className="mx-auto h-full"
@@ -13,7 +13,7 @@ const ChessBoard: React.FC<ChessboardProps> = ({
   orientation = 'white',
   showNotation = true,
   // sparePieces = false,
-  // draggable = true,
+  draggable = true,
   // undo = false,
   // dropOffBoard = "snapback",
   // transitionDuration = 300,
@@ -67,6 +67,7 @@ const ChessBoard: React.FC<ChessboardProps> = ({
           darkSquareStyle={darkSquareStyle}
           boardStyle={boardStyle}
           onDragOverSquare={onDragOverSquare}
+          draggable={draggable}
           onDrop={onDrop}
           onMouseOutSquare={onMouseOutSquare}
           onMouseOverSquare={onMouseOverSquare}
@@ -7,10 +7,13 @@ import {
   PositionStyles,
   MoveList,
   MetaGame,
+  EngineSettings,
+  GameOptions,
   MoveType,
   Square,
   Position,
   Piece,
+  ChessError,
 } from 'types';
 import {
   Button,
@@ -110,12 +113,28 @@ const highlightSquares = (
   return props;
 };
 
+const defaultEngine: EngineSettings = {
+  depth: 3,
+  node_limit: null,
+  move_time: 5000,
+  skill_level: 20,
+};
+
 const AskNewGame: React.FC<{
   setPosition: (position: Position) => void;
   setGameMeta: (meta: MetaGame) => void;
   isOpen: boolean;
   toggle: () => void;
 }> = ({ setPosition, setGameMeta, isOpen, toggle }) => {
+  const startGame = (options: GameOptions) => {
+    invoke<BoardStateArray>('new_game', { options }).then((board) => {
+      notify('starting new game', 'new_game');
+      setPosition(parseBoardState(board));
+      console.log(board);
+    });
+    invoke<MetaGame>('get_score').then((meta) => setGameMeta(meta));
+    toggle();
+  };
   return (
     <Modal toggle={toggle} isOpen={isOpen} animate position="extraLarge">
       <ModalHeader>Welcome to Tauri Chess</ModalHeader>
@@ -125,15 +144,61 @@ const AskNewGame: React.FC<{
           Cancel
         </Button>
         <Button
-          onClick={() => {
-            invoke<BoardStateArray>('new_game').then((board) => {
-              notify('starting new game', 'new_game');
-              setPosition(parseBoardState(board));
-              console.log(board);
-            });
-            invoke<MetaGame>('get_score').then((meta) => setGameMeta(meta));
-            toggle();
-          }}
+          className="mr-2"
+          onClick={() =>
+            startGame({
+              auto_claim_draws: false,
+              computer: 'Black',
+              engine: defaultEngine,
+              start: 'Standard',
+              auto_queen: false,
+              time_control: null,
+            })
+          }
+        >
+          Vs Computer
+        </Button>
+        <Button
+          className="mr-2"
+          onClick={() =>
+            startGame({
+              auto_claim_draws: false,
+              computer: null,
+              engine: defaultEngine,
+              start: 'RandomChess960',
+              auto_queen: false,
+              time_control: null,
+            })
+          }
+        >
+          Chess960
+        </Button>
+        <Button
+          className="mr-2"
+          onClick={() =>
+            startGame({
+              auto_claim_draws: false,
+              computer: null,
+              engine: defaultEngine,
+              start: 'Standard',
+              auto_queen: false,
+              time_control: { white: '300+2', black: '300+2' },
+            })
+          }
+        >
+          Blitz 5+2
+        </Button>
+        <Button
+          onClick={() =>
+            startGame({
+              auto_claim_draws: false,
+              computer: null,
+              engine: defaultEngine,
+              start: 'Standard',
+              auto_queen: false,
+              time_control: null,
+            })
+          }
         >
           New
         </Button>
@@ -142,6 +207,30 @@ const AskNewGame: React.FC<{
   );
 };
 
+/** Describe an error returned by a command for the player */
+const describeError = (error: ChessError): string => {
+  switch (error.kind) {
+    case 'NotYourTurn':
+      return "It's not your turn";
+    case 'GameOver':
+      return 'The game is over';
+    case 'NothingToUndo':
+      return 'There is no move to undo';
+    case 'NothingToRedo':
+      return 'There is no move to redo';
+    case 'NoDrawToClaim':
+      return 'There is no draw to claim';
+    case 'TimeRemaining':
+      return `${error.message} still has time left`;
+    case 'StateUnavailable':
+      return `The ${error.message} is unavailable, please restart`;
+    case 'ShuttingDown':
+      return 'The app is closing';
+    default:
+      return error.message;
+  }
+};
+
 const getGameState = (setPosition: (positions: Position) => void) => {
   invoke<BoardStateArray>('get_state').then((board) => {
     console.log(board);
@@ -155,4 +244,5 @@ export {
   AskNewGame,
   getGameState,
   coordToSquare,
+  describeError,
 };
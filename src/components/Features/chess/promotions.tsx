@@ -1,26 +1,35 @@
 import { Button, Modal, ModalBody, ModalHeader } from 'components/Elements';
-import { invoke } from '@tauri-apps/api/core';
 
 type PromotionProps = {
   toggle: (isOpen?: boolean | undefined) => void;
   isOpen: boolean;
+  /** called with the letter of the chosen piece, as used in UCI moves i.e. 'n' */
+  onChoice: (letter: string) => void;
 };
 
-export default function Promotions({ toggle, isOpen }: PromotionProps) {
-  const promotions = ['Queen', 'Knight', 'Rook', 'Bishop'];
+export default function Promotions({
+  toggle,
+  isOpen,
+  onChoice,
+}: PromotionProps) {
+  const promotions = [
+    ['Queen', 'q'],
+    ['Knight', 'n'],
+    ['Rook', 'r'],
+    ['Bishop', 'b'],
+  ];
   return (
     <Modal toggle={toggle} isOpen={isOpen} position="extraLarge">
       <ModalHeader>Pick Promotion</ModalHeader>
       <ModalBody>
         <div className="flex justify-evenly">
-          {promotions.map((promotion, index) => (
+          {promotions.map(([promotion, letter], index) => (
             <div className="hover:animate-heartBeat" key={promotion + index}>
               <Button
                 color="primary"
                 key={promotion}
                 onClick={() => {
-                  // tell Rust that we want this choice of promotion piece
-                  invoke('promote', { choice: promotion[0] }); // just first letter
+                  onChoice(letter);
                   toggle();
                 }}
               >
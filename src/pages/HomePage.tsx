@@ -6,6 +6,9 @@ import type {
   MoveList,
   PositionStyles,
   MetaGame,
+  ClockReading,
+  ChessError,
+  Color,
 } from 'types';
 import { Button } from 'components/Elements';
 import {
@@ -13,14 +16,27 @@ import {
   highlightSquares,
   parseBoardState,
   AskNewGame,
+  describeError,
 } from 'components/Features/chess';
 import Chessboard from 'components/Features/Chessboard';
 import { useToggle } from 'hooks';
 import { cn } from 'utils';
 import Promotions from 'components/Features/chess/promotions';
+import { notify } from 'services/notifications';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 
+/** Show milliseconds as M:SS, or H:MM:SS for an hour or more */
+const formatClock = (millis: number) => {
+  const seconds = Math.ceil(millis / 1000);
+  const pad = (n: number) => n.toString().padStart(2, '0');
+  const hours = Math.floor(seconds / 3600);
+  const minutes = Math.floor(seconds / 60) % 60;
+  return hours > 0
+    ? `${hours}:${pad(minutes)}:${pad(seconds % 60)}`
+    : `${minutes}:${pad(seconds % 60)}`;
+};
+
 const HomePage: React.FC = () => {
   const [newGameIsOpen, newGameToggle] = useToggle(true);
   const [promoterIsOpen, promoterToggle] = useToggle(false);
@@ -28,9 +44,13 @@ const HomePage: React.FC = () => {
   const [gameMeta, setGameMeta] = useState<MetaGame>({
     score: 0,
     turn: 0,
-    game_over: false,
+    halfmove_clock: 0,
+    result: 'Ongoing',
+    repetitions: 1,
+    claimable_draw: null,
+    auto_claim_draws: false,
+    chess960: false,
     en_passant: null,
-    promotable_pawn: null,
     white_king: {
       piece: {
         King: ['White', true, false, false],
@@ -49,6 +69,9 @@ const HomePage: React.FC = () => {
   const [whiteTurn, setWhiteTurn] = useState<boolean>(true);
   const [hoveredSquare, setHoveredSquare] = useState<Square>();
   const [rotation, setRotation] = useState(false);
+  const [clock, setClock] = useState<ClockReading | null>(null);
+  // move waiting for a promotion piece, in UCI form i.e. e7e8
+  const [promotionMove, setPromotionMove] = useState<string>();
 
   useEffect(() => {
     // ask if we want to start a new game
@@ -56,16 +79,39 @@ const HomePage: React.FC = () => {
       setPosition(parseBoardState(board)),
     );
     invoke<MetaGame>('get_score').then((meta) => setGameMeta(meta));
+    // a game resumed from the last session carries on without asking
+    invoke<string[]>('get_move_list').then((moves) => {
+      if (moves.length > 0) newGameToggle(false);
+    });
     // listen for promotion requests
-    const promRef = listen<string>('promotion', () => promoterToggle());
+    const promRef = listen<string>('promotion', (event) => {
+      setPromotionMove(event.payload);
+      promoterToggle(true);
+    });
     // listen for unexpected board state updates
     const boardRef = listen<BoardStateArray>('board', (event) => {
       console.log('Rust requests a boardstate update');
       setPosition(parseBoardState(event.payload));
+      invoke<MetaGame>('get_score').then((meta) => setGameMeta(meta));
     });
+    // the backend runs the clock and sends its reading while it is running
+    invoke<ClockReading | null>('get_clock').then((reading) => setClock(reading));
+    const clockRef = listen<ClockReading>('clock', (event) =>
+      setClock(event.payload),
+    );
+    const checkRef = listen<Color>('check', (event) =>
+      notify(`${event.payload} is in check`),
+    );
+    // a flag may fall between moves
+    const gameOverRef = listen('game_over', () =>
+      invoke<MetaGame>('get_score').then((meta) => setGameMeta(meta)),
+    );
     return () => {
       promRef.then((f) => f());
       boardRef.then((f) => f());
+      clockRef.then((f) => f());
+      gameOverRef.then((f) => f());
+      checkRef.then((f) => f());
     };
   }, []);
 
@@ -79,10 +125,46 @@ const HomePage: React.FC = () => {
           isOpen={newGameIsOpen}
           toggle={newGameToggle}
         />
-        <Promotions isOpen={promoterIsOpen} toggle={promoterToggle} />
+        <Promotions
+          isOpen={promoterIsOpen}
+          toggle={promoterToggle}
+          onChoice={(letter) => {
+            // play the whole move again, now with its promotion piece
+            invoke<[MoveList, BoardStateArray, MetaGame]>('enter_move', {
+              notation: promotionMove + letter,
+            })
+              .then(([, board, gameMeta]) => {
+                setSquareStyles(undefined);
+                setPosition(parseBoardState(board));
+                setGameMeta(gameMeta);
+                if (gameMeta.result !== 'Ongoing') newGameToggle(); // ask if we want to start a new game
+              })
+              .catch((error: ChessError) => notify(describeError(error)));
+          }}
+        />
         <Chessboard
           orientation={whiteTurn ? 'white' : 'black'}
-          draggable={false}
+          draggable={gameMeta.result === 'Ongoing'}
+          onDrop={({ sourceSquare, targetSquare, piece }) => {
+            invoke<[MoveList, BoardStateArray, MetaGame]>('drop_square', {
+              sourceSquare: sourceSquare,
+              targetSquare: targetSquare,
+              piece: piece,
+            })
+              .then(([sq, board, gameMeta]) => {
+                setSquareStyles(highlightSquares(sq, targetSquare));
+                setPosition(parseBoardState(board));
+                setGameMeta(gameMeta);
+                if (rotation) {
+                  gameMeta.turn % 2 == 0
+                    ? setWhiteTurn(true)
+                    : setWhiteTurn(false);
+                }
+                if (gameMeta.result !== 'Ongoing') newGameToggle(); // ask if we want to start a new game
+              })
+              // an illegal move leaves the piece where it was
+              .catch((error: ChessError) => notify(describeError(error)));
+          }}
           id="testBoard"
           width={400}
           showNotation
@@ -103,20 +185,22 @@ const HomePage: React.FC = () => {
           squareStyles={squareStyles}
           dropSquareStyle={undefined}
           onSquareClick={(square) => {
-            if (!gameMeta.game_over) {
+            if (gameMeta.result === 'Ongoing') {
               invoke<[MoveList, BoardStateArray, MetaGame]>('click_square', {
                 square: square,
-              }).then(([sq, board, gameMeta]) => {
-                setSquareStyles(highlightSquares(sq, square));
-                setPosition(parseBoardState(board));
-                setGameMeta(gameMeta);
-                if (rotation) {
-                  gameMeta.turn % 2 == 0
-                    ? setWhiteTurn(true)
-                    : setWhiteTurn(false);
-                }
-                if (gameMeta.game_over) newGameToggle(); // ask if we want to start a new game
-              });
+              })
+                .then(([sq, board, gameMeta]) => {
+                  setSquareStyles(highlightSquares(sq, square));
+                  setPosition(parseBoardState(board));
+                  setGameMeta(gameMeta);
+                  if (rotation) {
+                    gameMeta.turn % 2 == 0
+                      ? setWhiteTurn(true)
+                      : setWhiteTurn(false);
+                  }
+                  if (gameMeta.result !== 'Ongoing') newGameToggle(); // ask if we want to start a new game
+                })
+                .catch((error: ChessError) => notify(describeError(error)));
             }
           }}
         />
@@ -153,6 +237,12 @@ const HomePage: React.FC = () => {
           score: {gameMeta.score}, turn: {gameMeta.turn} (
           {gameMeta.turn % 2 == 0 ? 'White' : 'Black'})
         </p>
+        {clock && (
+          <p className="inline border border-black rounded-sm px-6 py-3 text-sm mr-2">
+            white: {formatClock(clock.white)}, black:{' '}
+            {formatClock(clock.black)}
+          </p>
+        )}
         <Button
           className="mr-2"
           onClick={() => {
@@ -168,6 +258,28 @@ const HomePage: React.FC = () => {
         >
           {rotation ? 'Rotation On' : 'Rotation Off'}
         </Button>
+        <Button
+          className="mr-2"
+          onClick={() =>
+            invoke('save_game').then(() => notify('game saved', 'save_game'))
+          }
+        >
+          Save
+        </Button>
+        <Button
+          onClick={() =>
+            invoke<BoardStateArray>('load_game').then((board) => {
+              setPosition(parseBoardState(board));
+              setSquareStyles(undefined);
+              invoke<MetaGame>('get_score').then((meta) => setGameMeta(meta));
+              invoke<ClockReading | null>('get_clock').then((reading) =>
+                setClock(reading),
+              );
+            })
+          }
+        >
+          Load
+        </Button>
       </div>
     </div>
   );
@@ -0,0 +1,713 @@
+void TAURI_CHANNEL;
+void __makeEvents__;
+// This file was generated by [tauri-specta](https://github.com/oscartbeaumont/tauri-specta). Do not edit this file manually.
+
+/** user-defined commands **/
+
+
+export const commands = {
+/**
+ * Initialize a new game by sending a starting set of coords
+ */
+async newGame(options: GameOptions | null) : Promise<Result<BoardState, ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("new_game", { options }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Get the location of all pieces from global memory
+ */
+async getState() : Promise<Result<BoardState, ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_state") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Get the game score from global memory
+ */
+async getScore() : Promise<Result<GameMeta, ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_score") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Set up the board from a FEN string, replacing the current game
+ */
+async loadFen(fen: string) : Promise<Result<[BoardState, GameMeta], ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("load_fen", { fen }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Get the current position as a FEN string
+ */
+async getFen() : Promise<Result<string, ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_fen") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Replay a game from PGN, replacing the current game
+ */
+async loadPgn(pgn: string) : Promise<Result<[BoardState, GameMeta], ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("load_pgn", { pgn }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Get the game so far as PGN
+ */
+async getPgn() : Promise<Result<string, ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_pgn") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Highlight available moves for the piece occupying this square
+ */
+async hoverSquare(square: string) : Promise<Result<([[number, number], MoveType])[], ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("hover_square", { square }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Remove any highlighting for square just left
+ */
+async unhoverSquare(square: string) : Promise<boolean> {
+    return await TAURI_INVOKE("unhover_square", { square });
+},
+/**
+ * Move a piece dragged from one square and dropped on another, if that is legal
+ * 
+ * `piece` is the piece the UI thinks is being moved, i.e. wN, which must be the one on the board.
+ */
+async dropSquare(sourceSquare: string, targetSquare: string, piece: string) : Promise<Result<[([[number, number], MoveType])[], BoardState, GameMeta], ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("drop_square", { sourceSquare, targetSquare, piece }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Click on a square to select or deselect it.
+ * 
+ * If a square is selected, the hover command will be deactivated.
+ * If a square is a valid move of the selected piece, move that piece.
+ */
+async clickSquare(square: string) : Promise<Result<[([[number, number], MoveType])[], BoardState, GameMeta], ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("click_square", { square }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Make a move typed in SAN or long algebraic notation, i.e. Nf3, exd5, e7e8q or O-O
+ */
+async enterMove(notation: string) : Promise<Result<[([[number, number], MoveType])[], BoardState, GameMeta], ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("enter_move", { notation }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Get the moves played so far in SAN
+ */
+async getMoveList() : Promise<Result<string[], ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_move_list") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Take back the last move
+ * 
+ * Against the computer its reply is taken back as well, so it is the player's turn again.
+ */
+async undoMove() : Promise<Result<[BoardState, GameMeta], ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("undo_move") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Play the last move taken back again
+ * 
+ * Against the computer its reply is played again too, or searched for if there wasn't one.
+ */
+async redoMove() : Promise<Result<[BoardState, GameMeta], ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("redo_move") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Hash of the current position as hex, and how many times it has occurred
+ */
+async getPositionHash() : Promise<Result<[string, number], ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_position_hash") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * End the game in a draw, if the player to move is able to claim one
+ */
+async claimDraw() : Promise<Result<GameMeta, ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("claim_draw") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * A player's clock has run out, losing them the game unless their opponent can't mate
+ * 
+ * Timed games keep their own clock, so only a flag that has really fallen counts.
+ */
+async timeOut(color: Color) : Promise<Result<GameMeta, ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("time_out", { color }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Read the clock of a timed game
+ */
+async getClock() : Promise<Result<ClockReading | null, ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_clock") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Save the game to the app data directory, to be loaded again later
+ */
+async saveGame() : Promise<Result<null, ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("save_game") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Load the game last saved with `save_game`, along with the options it was played with
+ */
+async loadGame() : Promise<Result<BoardState, ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("load_game") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Let the computer choose and play a move for the player to move
+ * 
+ * The search runs on its own thread, and the move is sent back through the event queue.
+ */
+async playEngineMove() : Promise<Result<null, ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("play_engine_move") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Start an external UCI engine, which then plays and analyses instead of the built-in one
+ * 
+ * Returns the name of the engine.
+ */
+async startUciEngine(path: string) : Promise<Result<string, ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("start_uci_engine", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Shut down the external UCI engine, going back to the built-in one
+ */
+async stopUciEngine() : Promise<Result<null, ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("stop_uci_engine") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Analyse the current position with the external UCI engine
+ * 
+ * Progress is streamed as `uci_info` events with the line in SAN, then the best move
+ * is sent as a `uci_bestmove` event.
+ */
+async analysePosition(limit: UciLimit) : Promise<Result<null, ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("analyse_position", { limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async eventTester() : Promise<Result<null, ChessError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("event_tester") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+}
+}
+
+/** user-defined events **/
+
+
+
+/** user-defined constants **/
+
+
+
+/** user-defined types **/
+
+/**
+ * 8x8 array containing either pieces or nothing
+ */
+export type BoardState = [([Piece, Piece, Piece, Piece, Piece, Piece, Piece, Piece]), ([Piece, Piece, Piece, Piece, Piece, Piece, Piece, Piece]), ([Piece, Piece, Piece, Piece, Piece, Piece, Piece, Piece]), ([Piece, Piece, Piece, Piece, Piece, Piece, Piece, Piece]), ([Piece, Piece, Piece, Piece, Piece, Piece, Piece, Piece]), ([Piece, Piece, Piece, Piece, Piece, Piece, Piece, Piece]), ([Piece, Piece, Piece, Piece, Piece, Piece, Piece, Piece]), ([Piece, Piece, Piece, Piece, Piece, Piece, Piece, Piece])]
+/**
+ * Everything that can go wrong in a command, so the frontend can tell the player exactly what
+ */
+export type ChessError = 
+/**
+ * A square name that isn't on the board, i.e. a9
+ */
+{ kind: "InvalidSquare"; message: string } | 
+/**
+ * A move the rules don't allow, or notation that can't be read as a move
+ */
+{ kind: "IllegalMove"; message: string } | 
+/**
+ * A piece belonging to the player who isn't to move
+ */
+{ kind: "NotYourTurn" } | 
+/**
+ * A dragged piece that isn't the one on the square it came from
+ */
+{ kind: "WrongPiece"; message: string } | 
+/**
+ * A promotion piece given for a move that isn't a promotion, or one a pawn can't become
+ */
+{ kind: "InvalidPromotion"; message: string } | 
+/**
+ * The game has already ended
+ */
+{ kind: "GameOver" } | 
+/**
+ * There is no move to take back
+ */
+{ kind: "NothingToUndo" } | 
+/**
+ * There is no move taken back to play again
+ */
+{ kind: "NothingToRedo" } | 
+/**
+ * The player to move has no draw they are able to claim
+ */
+{ kind: "NoDrawToClaim" } | 
+/**
+ * A player whose flag hasn't fallen was said to have run out of time
+ */
+{ kind: "TimeRemaining"; message: Color } | 
+/**
+ * A FEN, PGN or Chess960 starting position that couldn't be set up
+ */
+{ kind: "InvalidPosition"; message: string } | 
+/**
+ * The external UCI engine failed, or isn't running
+ */
+{ kind: "Engine"; message: string } | 
+/**
+ * A game couldn't be saved or loaded
+ */
+{ kind: "Save"; message: string } | 
+/**
+ * Shared state left unusable by a panic on another thread
+ */
+{ kind: "StateUnavailable"; message: string } | 
+/**
+ * The app is closing, so events can no longer be sent to the frontend
+ */
+{ kind: "ShuttingDown" } | 
+/**
+ * Events couldn't be sent to the frontend, or anything else unexpected
+ */
+{ kind: "Internal"; message: string }
+/**
+ * What the clock shows at a moment in time
+ */
+export type ClockReading = { 
+/**
+ * Milliseconds left to White
+ */
+white: number; 
+/**
+ * Milliseconds left to Black
+ */
+black: number; 
+/**
+ * The side whose clock is running
+ */
+running: Color | null }
+export type Color = "Black" | "White"
+/**
+ * Strength of the computer opponent, searching until the first limit is reached
+ */
+export type EngineSettings = { 
+/**
+ * Deepest search, in moves by either player
+ */
+depth: number; 
+/**
+ * Most positions to look at per move
+ */
+node_limit: number | null; 
+/**
+ * Most time to think per move, in milliseconds
+ */
+move_time: number | null; 
+/**
+ * From 0, picking loosely among the better moves, up to 20, always playing the best one found
+ */
+skill_level: number }
+/**
+ * Something that happened in the backend which the frontend didn't ask for, i.e. the computer
+ * replying or a clock running down
+ */
+export type GameEvent = 
+/**
+ * The pieces have moved other than by a command from the UI
+ */
+{ BoardChanged: BoardState } | 
+/**
+ * A move was played, in SAN
+ */
+{ MoveMade: string } | 
+/**
+ * The player to move is in check
+ */
+{ Check: Color } | 
+/**
+ * The game has just come to an end
+ */
+{ GameOver: GameResult } | 
+/**
+ * A pawn is to be promoted, carrying the move in UCI form, i.e. e7e8
+ */
+{ PromotionRequired: string } | 
+/**
+ * The clock of a running game
+ */
+{ ClockTick: ClockReading } | 
+/**
+ * Progress of the external engine's analysis, with the line in SAN
+ */
+{ EngineInfo: UciInfo } | 
+/**
+ * The move the external engine settled on in its analysis, in SAN
+ */
+{ EngineBestMove: string | null } | 
+/**
+ * Sent by the test page
+ */
+{ Test: string }
+export type GameMeta = { 
+/**
+ * Game turn
+ */
+turn: number; 
+/**
+ * Game score as a relative sum of piece value
+ */
+score: number; 
+/**
+ * Number of halfmoves since the last capture or pawn advance
+ */
+halfmove_clock: number; 
+/**
+ * Register if a pawn that has done a double move in the last turn
+ */
+en_passant: [number, number] | null; 
+/**
+ * Metadata relating to the black King
+ */
+black_king: KingMeta; 
+/**
+ * Metadata relating to the white King
+ */
+white_king: KingMeta; 
+/**
+ * Register if the game is active or how it ended
+ */
+result: GameResult; 
+/**
+ * Number of times the current position has occurred
+ */
+repetitions: number; 
+/**
+ * A draw the player to move is able to claim
+ */
+claimable_draw: Termination | null; 
+/**
+ * Declare draws as soon as they could be claimed, instead of waiting for a claim
+ */
+auto_claim_draws: boolean; 
+/**
+ * Chess960 rules, where castling is written as the king moving onto its own rook
+ */
+chess960?: boolean }
+/**
+ * Settings chosen when starting a new game
+ */
+export type GameOptions = { 
+/**
+ * Declare draws as soon as they could be claimed, instead of waiting for a claim
+ */
+auto_claim_draws: boolean; 
+/**
+ * Colour played by the computer, if playing against it
+ */
+computer: Color | null; 
+/**
+ * How strongly the computer plays
+ */
+engine: EngineSettings; 
+/**
+ * The position the game starts from
+ */
+start: StartPosition; 
+/**
+ * Promote pawns to a queen without asking which piece
+ */
+auto_queen: boolean; 
+/**
+ * Time for each player, if the game is timed
+ */
+time_control: TimeControls | null }
+/**
+ * Outcome of the game, or that it is still being played
+ */
+export type GameResult = "Ongoing" | { WhiteWins: Termination } | { BlackWins: Termination } | { Draw: Termination }
+export type KingMeta = { 
+/**
+ * Clone of the King's Piece Struct
+ */
+piece: Piece; 
+/**
+ * Current location of the King
+ */
+square: [number, number] }
+/**
+ * Moves can take one of many special types
+ */
+export type MoveType = "Move" | "Capture" | "Castle" | "EnPassant" | "Double"
+export type Piece = "None" | { Pawn: [Color, boolean] } | { King: [Color, boolean, boolean, boolean] } | { Queen: [Color, boolean] } | { Bishop: [Color, boolean] } | { Knight: [Color, boolean] } | { Rook: [Color, boolean] }
+/**
+ * Which starting position a new game uses
+ */
+export type StartPosition = "Standard" | 
+/**
+ * One of the Chess960 arrays, numbered 0 to 959
+ */
+{ Chess960: number } | 
+/**
+ * A Chess960 array picked at random
+ */
+"RandomChess960"
+/**
+ * Reason the game came to an end
+ */
+export type Termination = 
+/**
+ * The player to move is in check with no legal moves
+ */
+"Checkmate" | 
+/**
+ * The player to move is not in check but has no legal moves
+ */
+"Stalemate" | 
+/**
+ * The same position has occurred three times, and a draw was claimed
+ */
+"ThreefoldRepetition" | 
+/**
+ * The same position has occurred five times
+ */
+"FivefoldRepetition" | 
+/**
+ * Fifty moves by each player without a capture or pawn move, and a draw was claimed
+ */
+"FiftyMoveRule" | 
+/**
+ * Seventy-five moves by each player without a capture or pawn move
+ */
+"SeventyFiveMoveRule" | 
+/**
+ * Neither player has enough pieces left to checkmate
+ */
+"InsufficientMaterial" | 
+/**
+ * The player ran out of time
+ */
+"Timeout" | 
+/**
+ * The player ran out of time, but their opponent couldn't checkmate them
+ */
+"TimeoutVsInsufficientMaterial"
+/**
+ * The time control for each player, who may be given different amounts of time
+ */
+export type TimeControls = { white: string; black: string }
+/**
+ * Progress report sent by the engine while it searches
+ */
+export type UciInfo = { 
+/**
+ * Depth searched so far
+ */
+depth: number | null; 
+/**
+ * Score of the best line found
+ */
+score: UciScore | null; 
+/**
+ * The line of play the engine expects
+ */
+pv: string[] }
+/**
+ * How long the engine should search for
+ */
+export type UciLimit = 
+/**
+ * Search this many moves deep
+ */
+{ Depth: number } | 
+/**
+ * Search for this many milliseconds
+ */
+{ MoveTime: number }
+/**
+ * The engine's opinion of the position, for the player to move
+ */
+export type UciScore = 
+/**
+ * Advantage in hundredths of a pawn
+ */
+{ Centipawns: number } | 
+/**
+ * Mate in this many moves, negative if the player to move is getting mated
+ */
+{ Mate: number }
+
+/** tauri-specta globals **/
+
+import {
+	invoke as TAURI_INVOKE,
+	Channel as TAURI_CHANNEL,
+} from "@tauri-apps/api/core";
+import * as TAURI_API_EVENT from "@tauri-apps/api/event";
+import { type WebviewWindow as __WebviewWindow__ } from "@tauri-apps/api/webviewWindow";
+
+type __EventObj__<T> = {
+	listen: (
+		cb: TAURI_API_EVENT.EventCallback<T>,
+	) => ReturnType<typeof TAURI_API_EVENT.listen<T>>;
+	once: (
+		cb: TAURI_API_EVENT.EventCallback<T>,
+	) => ReturnType<typeof TAURI_API_EVENT.once<T>>;
+	emit: null extends T
+		? (payload?: T) => ReturnType<typeof TAURI_API_EVENT.emit>
+		: (payload: T) => ReturnType<typeof TAURI_API_EVENT.emit>;
+};
+
+export type Result<T, E> =
+	| { status: "ok"; data: T }
+	| { status: "error"; error: E };
+
+function __makeEvents__<T extends Record<string, any>>(
+	mappings: Record<keyof T, string>,
+) {
+	return new Proxy(
+		{} as unknown as {
+			[K in keyof T]: __EventObj__<T[K]> & {
+				(handle: __WebviewWindow__): __EventObj__<T[K]>;
+			};
+		},
+		{
+			get: (_, event) => {
+				const name = mappings[event as keyof T];
+
+				return new Proxy((() => {}) as any, {
+					apply: (_, __, [window]: [__WebviewWindow__]) => ({
+						listen: (arg: any) => window.listen(name, arg),
+						once: (arg: any) => window.once(name, arg),
+						emit: (arg: any) => window.emit(name, arg),
+					}),
+					get: (_, command: keyof __EventObj__<any>) => {
+						switch (command) {
+							case "listen":
+								return (arg: any) => TAURI_API_EVENT.listen(name, arg);
+							case "once":
+								return (arg: any) => TAURI_API_EVENT.once(name, arg);
+							case "emit":
+								return (arg: any) => TAURI_API_EVENT.emit(name, arg);
+						}
+					},
+				});
+			},
+		},
+	);
+}
@@ -8,43 +8,38 @@ import type {
   Position,
   Square,
 } from './chessboard';
+import type {
+  BoardState,
+  ChessError,
+  ClockReading,
+  Color,
+  EngineSettings,
+  GameEvent,
+  GameMeta,
+  GameOptions,
+  GameResult,
+  MoveType,
+  Piece as BindingsPiece,
+  StartPosition,
+  Termination,
+  TimeControls,
+  UciInfo,
+  UciLimit,
+  UciScore,
+} from './bindings';
 
 type PositionStyles = {
   [pos in Square]?: CSSProperties | undefined;
 };
-type BoardStateArray = string[][];
-type Color = 'White' | 'Black';
 type PieceType = 'Queen' | 'King' | 'Bishop' | 'Knight' | 'Rook' | 'Pawn';
-type FirstMove = boolean;
-type Check = boolean;
-type CheckMate = boolean;
-type RustPiece =
-  | { Queen: [Color, FirstMove] }
-  | { King: [Color, FirstMove, Check, CheckMate] }
-  | { Bishop: [Color, FirstMove] }
-  | { Knight: [Color, FirstMove] }
-  | { Rook: [Color, FirstMove] }
-  | { Pawn: [Color, FirstMove] };
 
-type MoveType = 'Move' | 'Capture' | 'Castle' | 'EnPassant' | 'Double';
+// the types crossing from Rust are generated, see src-tauri/src/chess/bindings.rs
+type BoardStateArray = BoardState;
+type RustPiece = Exclude<BindingsPiece, 'None'>;
 
 type MoveList = [[number, number], MoveType][];
 
-type MetaGame = {
-  score: number;
-  turn: number;
-  game_over: boolean;
-  en_passant: [number, number] | null;
-  promotable_pawn: [number, number] | null;
-  black_king: {
-    piece: { King: [Color, FirstMove, Check, CheckMate] };
-    square: [number, number];
-  };
-  white_king: {
-    piece: { King: [Color, FirstMove, Check, CheckMate] };
-    square: [number, number];
-  };
-};
+type MetaGame = GameMeta;
 
 export type {
   BoardStateArray,
@@ -54,7 +49,19 @@ export type {
   RustPiece,
   MoveType,
   MoveList,
+  Termination,
+  GameResult,
+  EngineSettings,
+  StartPosition,
+  TimeControls,
+  ClockReading,
+  ChessError,
+  GameOptions,
+  UciScore,
+  UciInfo,
+  UciLimit,
   MetaGame,
+  GameEvent,
   // from chessboard.ts
   ChessboardProps,
   CustomPieces,